@@ -1,7 +1,7 @@
 use criterion::{Criterion, criterion_group, criterion_main};
 
 fn day01_benchmark(c: &mut Criterion) {
-    let input = include_str!("../input/day01/input.txt");
+    let input = &adventofcode2025::load_or_generate_input(1).unwrap();
     c.bench_function("day01part1", |b| {
         b.iter(|| adventofcode2025::day01::solve_part1(input))
     });
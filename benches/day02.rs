@@ -1,7 +1,7 @@
 use criterion::{Criterion, criterion_group, criterion_main};
 
 fn day02_benchmark(c: &mut Criterion) {
-    let input = include_str!("../input/day02/input.txt");
+    let input = &adventofcode2025::load_or_generate_input(2).unwrap();
     c.bench_function("day02part1", |b| {
         b.iter(|| adventofcode2025::day02::solve_part1(input))
     });
@@ -1,7 +1,7 @@
 use criterion::{Criterion, criterion_group, criterion_main};
 
 fn day03_benchmark(c: &mut Criterion) {
-    let input = include_str!("../input/day03/input.txt");
+    let input = &adventofcode2025::load_or_generate_input(3).unwrap();
     c.bench_function("day03part1", |b| {
         b.iter(|| adventofcode2025::day03::solve_part1(input))
     });
@@ -1,14 +1,40 @@
-use criterion::{Criterion, criterion_group, criterion_main};
+use adventofcode2025::{GeneratorOptions, generate_input};
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
 
 fn day04_benchmark(c: &mut Criterion) {
-    let input = include_str!("../input/day04/input.txt");
+    let input = &adventofcode2025::load_or_generate_input(4).unwrap();
     c.bench_function("day04part1", |b| {
         b.iter(|| adventofcode2025::day04::solve_part1(input))
     });
     c.bench_function("day04part2", |b| {
         b.iter(|| adventofcode2025::day04::solve_part2(input))
     });
+    c.bench_function("day04part1bitset", |b| {
+        b.iter(|| adventofcode2025::day04::solve_part1_with_bitset(input))
+    });
+    c.bench_function("day04part2bitset", |b| {
+        b.iter(|| adventofcode2025::day04::solve_part2_with_bitset(input))
+    });
+}
+
+fn day04_generated_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("day04generated");
+    for size in [256, 1024, 2048] {
+        let options = GeneratorOptions {
+            seed: 0,
+            size,
+            width: None,
+        };
+        let input = generate_input(4, &options).unwrap();
+        group.bench_with_input(BenchmarkId::new("part2", size), &input, |b, input| {
+            b.iter(|| adventofcode2025::day04::solve_part2(input))
+        });
+        group.bench_with_input(BenchmarkId::new("part2bitset", size), &input, |b, input| {
+            b.iter(|| adventofcode2025::day04::solve_part2_with_bitset(input))
+        });
+    }
+    group.finish();
 }
 
-criterion_group!(benches, day04_benchmark);
+criterion_group!(benches, day04_benchmark, day04_generated_benchmark);
 criterion_main!(benches);
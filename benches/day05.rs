@@ -1,7 +1,7 @@
 use criterion::{Criterion, criterion_group, criterion_main};
 
 fn day05_benchmark(c: &mut Criterion) {
-    let input = include_str!("../input/day05/input.txt");
+    let input = &adventofcode2025::load_or_generate_input(5).unwrap();
     c.bench_function("day05part1", |b| {
         b.iter(|| adventofcode2025::day05::solve_part1(input))
     });
@@ -1,7 +1,7 @@
 use criterion::{Criterion, criterion_group, criterion_main};
 
 fn day06_benchmark(c: &mut Criterion) {
-    let input = include_str!("../input/day06/input.txt");
+    let input = &adventofcode2025::load_or_generate_input(6).unwrap();
     c.bench_function("day06part1", |b| {
         b.iter(|| adventofcode2025::day06::solve_part1(input))
     });
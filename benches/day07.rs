@@ -1,14 +1,42 @@
-use criterion::{Criterion, criterion_group, criterion_main};
+use adventofcode2025::{GeneratorOptions, generate_input};
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
 
 fn day07_benchmark(c: &mut Criterion) {
-    let input = include_str!("../input/day07/input.txt");
+    let input = &adventofcode2025::load_or_generate_input(7).unwrap();
     c.bench_function("day07part1", |b| {
         b.iter(|| adventofcode2025::day07::solve_part1(input))
     });
     c.bench_function("day07part2", |b| {
         b.iter(|| adventofcode2025::day07::solve_part2(input))
     });
+    c.bench_function("day07part1hashset", |b| {
+        b.iter(|| adventofcode2025::day07::solve_part1_with_hash_set(input))
+    });
+    c.bench_function("day07part2hashset", |b| {
+        b.iter(|| adventofcode2025::day07::solve_part2_with_hash_set(input))
+    });
+}
+
+fn day07_generated_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("day07generated");
+    for width in [256, 1024, 4096] {
+        let options = GeneratorOptions {
+            seed: 0,
+            size: 128,
+            width: Some(width),
+        };
+        let input = generate_input(7, &options).unwrap();
+        group.bench_with_input(BenchmarkId::new("part2", width), &input, |b, input| {
+            b.iter(|| adventofcode2025::day07::solve_part2(input))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("part2hashset", width),
+            &input,
+            |b, input| b.iter(|| adventofcode2025::day07::solve_part2_with_hash_set(input)),
+        );
+    }
+    group.finish();
 }
 
-criterion_group!(benches, day07_benchmark);
+criterion_group!(benches, day07_benchmark, day07_generated_benchmark);
 criterion_main!(benches);
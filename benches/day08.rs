@@ -1,7 +1,8 @@
-use criterion::{Criterion, criterion_group, criterion_main};
+use adventofcode2025::{GeneratorOptions, generate_input};
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
 
 fn day08_benchmark(c: &mut Criterion) {
-    let input = include_str!("../input/day08/input.txt");
+    let input = &adventofcode2025::load_or_generate_input(8).unwrap();
     c.bench_function("day08part1", |b| {
         b.iter(|| adventofcode2025::day08::solve_part1(input))
     });
@@ -10,5 +11,31 @@ fn day08_benchmark(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, day08_benchmark);
+fn day08_generated_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("day08generated");
+    group.sample_size(10);
+    for count in [1000, 2000] {
+        let options = GeneratorOptions {
+            seed: 0,
+            size: count,
+            width: None,
+        };
+        let input = generate_input(8, &options).unwrap();
+        group.bench_with_input(BenchmarkId::new("part1", count), &input, |b, input| {
+            b.iter(|| adventofcode2025::day08::solve_part1(input))
+        });
+        group.bench_with_input(BenchmarkId::new("part1heap", count), &input, |b, input| {
+            b.iter(|| adventofcode2025::day08::solve_part1_with_heap(input))
+        });
+        group.bench_with_input(BenchmarkId::new("part2", count), &input, |b, input| {
+            b.iter(|| adventofcode2025::day08::solve_part2(input))
+        });
+        group.bench_with_input(BenchmarkId::new("part2heap", count), &input, |b, input| {
+            b.iter(|| adventofcode2025::day08::solve_part2_with_heap(input))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, day08_benchmark, day08_generated_benchmark);
 criterion_main!(benches);
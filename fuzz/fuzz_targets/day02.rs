@@ -0,0 +1,22 @@
+#![no_main]
+
+use adventofcode2025::day02;
+use libfuzzer_sys::fuzz_target;
+
+/// The solver checks every single ID, larger ranges only slow the fuzzer down.
+const MAX_IDS: u128 = 100_000;
+
+// Any input is either solved or rejected with a ParseError, it never panics
+fuzz_target!(|input: &str| {
+    let Ok(ranges) = day02::load_ranges(input) else {
+        return;
+    };
+    let ids = ranges
+        .iter()
+        .map(|(start, end)| end - start)
+        .fold(0u128, u128::saturating_add);
+    if ids <= MAX_IDS {
+        let _ = day02::try_solve_part1(input);
+        let _ = day02::try_solve_part2(input);
+    }
+});
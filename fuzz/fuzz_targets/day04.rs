@@ -0,0 +1,15 @@
+#![no_main]
+
+use adventofcode2025::day04;
+use libfuzzer_sys::fuzz_target;
+
+// Any input is either solved or rejected with a ParseError, it never panics,
+// and the bitset backend agrees with the scalar one on every valid grid
+fuzz_target!(|input: &str| {
+    if let Ok(expected) = day04::try_solve_part1(input) {
+        assert_eq!(day04::solve_part1_with_bitset(input), expected);
+    }
+    if let Ok(expected) = day04::try_solve_part2(input) {
+        assert_eq!(day04::solve_part2_with_bitset(input), expected);
+    }
+});
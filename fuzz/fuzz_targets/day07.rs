@@ -0,0 +1,10 @@
+#![no_main]
+
+use adventofcode2025::day07;
+use libfuzzer_sys::fuzz_target;
+
+// Any input is either solved or rejected with a ParseError, it never panics
+fuzz_target!(|input: &str| {
+    let _ = day07::try_solve_part1(input);
+    let _ = day07::try_solve_part2(input);
+});
@@ -6,3 +6,140 @@ pub mod day05;
 pub mod day06;
 pub mod day07;
 pub mod day08;
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::{ParseError, registered_parts};
+
+    /// Reads the fuzzing corpus of a day, skipping inputs that are not UTF-8 like the fuzz targets do.
+    fn corpus(day: &str) -> Vec<String> {
+        fs::read_dir(format!("fuzz/corpus/{}", day))
+            .unwrap()
+            .filter_map(|entry| String::from_utf8(fs::read(entry.unwrap().path()).unwrap()).ok())
+            .collect()
+    }
+
+    macro_rules! replay_corpus {
+        ($($day:ident),+) => {$(
+            for input in corpus(stringify!($day)) {
+                let _ = super::$day::try_solve_part1(&input);
+                let _ = super::$day::try_solve_part2(&input);
+            }
+        )+};
+    }
+
+    /// Replays the fuzzing corpus, which holds every input that crashed a fuzz target,
+    /// so the fixes stay covered without a nightly toolchain.
+    #[test]
+    fn test_fuzz_corpus_never_panics() {
+        replay_corpus!(day01, day02, day03, day04, day05, day06, day07, day08);
+    }
+
+    /// Runs every alternative implementation on the fuzzing corpus, which reaches
+    /// edge cases like empty rows that the generators never produce.
+    #[test]
+    fn test_fuzz_corpus_implementations_agree() {
+        for part in registered_parts() {
+            for input in corpus(&format!("day{:02}", part.day)) {
+                if let Some(counterexample) = part.check(&input) {
+                    panic!("{}", counterexample);
+                }
+            }
+        }
+    }
+
+    /// A self-authored example of a day with hand-computed answers to both parts.
+    struct Example {
+        day: u32,
+        input: &'static str,
+        answers: [&'static str; 2],
+    }
+
+    #[rustfmt::skip]
+    const EXAMPLES: &[Example] = &[
+        // Full rotations that start and end on zero
+        Example { day: 1, input: "L50\nR100\nR250\nL30\nL20\nR300\nL125", answers: ["4", "9"] },
+        Example { day: 1, input: "R50\r\nL100\r\n", answers: ["2", "2"] },
+        // The last range overlaps the first one, its IDs count again
+        Example { day: 2, input: "10-30,99-111,1000-1020,5555-5560,123123-123124,20-25", answers: ["129842", "129953"] },
+        Example { day: 2, input: "11-22\n", answers: ["33", "33"] },
+        Example { day: 3, input: "123456789123456\r\n919191919191919\r\n", answers: ["195", "1456708315375"] },
+        // Banks of a single digit, every choice of batteries ties
+        Example { day: 3, input: "999999999999\n111111111111\n\n", answers: ["110", "1111111111110"] },
+        // A full 3x3 block is removed corners first, then edges, then its center,
+        // the two rolls on the right are isolated
+        Example { day: 4, input: "@@@.@\n@@@..\n@@@.@", answers: ["6", "11"] },
+        Example { day: 4, input: "@@\r\n@@\r\n", answers: ["4", "4"] },
+        // Overlapping and single ID ranges
+        Example { day: 5, input: "2-4\n8-12\n11-15\n20-20\n\n1\n3\n12\n15\n16\n20\n30", answers: ["4", "12"] },
+        Example { day: 5, input: "2-4\r\n\r\n\r\n3\r\n\r\n5\r\n", answers: ["1", "3"] },
+        // Numbers aligned to both sides of their problems
+        Example { day: 6, input: "12 3  45\n 4 56 6 \n*  +  + ", answers: ["158", "116"] },
+        // Ragged lines, the operator row is the shortest
+        Example { day: 6, input: "12 3\r\n 4 56\r\n*  +\r\n", answers: ["107", "65"] },
+        // The two beams of the first splitter meet again
+        Example { day: 7, input: "..S..\n.....\n..^..\n.....\n.^.^.\n.....", answers: ["3", "4"] },
+        // The second splitter is in the shadow of the first one
+        Example { day: 7, input: ".S.\r\n.^.\r\n.^.\r\n...\r\n", answers: ["1", "2"] },
+        // Three clusters without ties between their distances
+        Example { day: 8, input: "0,0,0\n1,0,0\n0,2,0\n10,0,0\n10,3,0\n30,0,0", answers: ["6", "300"] },
+        // Both connections are as long, the one with the lower ids comes first
+        Example { day: 8, input: "0,0,0\n1,0,0\n2,0,0\n", answers: ["3", "2"] },
+        Example { day: 8, input: "0,0,0\r\n\r\n3,0,0\r\n1,0,0\r\n", answers: ["3", "3"] },
+    ];
+
+    fn try_solve(day: u32, input: &str) -> Result<[String; 2], ParseError> {
+        macro_rules! answers {
+            ($day:ident) => {
+                [
+                    super::$day::try_solve_part1(input)?.to_string(),
+                    super::$day::try_solve_part2(input)?.to_string(),
+                ]
+            };
+        }
+
+        Ok(match day {
+            1 => answers!(day01),
+            2 => answers!(day02),
+            3 => answers!(day03),
+            4 => answers!(day04),
+            5 => answers!(day05),
+            6 => answers!(day06),
+            7 => answers!(day07),
+            8 => answers!(day08),
+            _ => unreachable!("There is no day {}", day),
+        })
+    }
+
+    /// Checks the examples against the solutions and every alternative implementation,
+    /// so a fresh checkout without the puzzle examples still covers every day.
+    #[test]
+    fn test_examples() {
+        for example in EXAMPLES {
+            assert_eq!(
+                try_solve(example.day, example.input).unwrap(),
+                example.answers,
+                "Day {}: {:?}",
+                example.day,
+                example.input
+            );
+            for part in registered_parts()
+                .iter()
+                .filter(|part| part.day == example.day)
+            {
+                for (name, outcome) in part.outcomes(example.input) {
+                    assert_eq!(
+                        outcome.as_deref(),
+                        Ok(example.answers[part.part as usize - 1]),
+                        "Day {} {}: {:?}",
+                        example.day,
+                        name,
+                        example.input
+                    );
+                }
+            }
+        }
+    }
+}
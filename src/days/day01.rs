@@ -1,3 +1,5 @@
+use crate::ParseError;
+
 const DIAL_SIZE: i32 = 100;
 
 struct Dial(i32);
@@ -26,6 +28,10 @@ impl Dial {
         if !self.is_zero() && (self.0 + steps > DIAL_SIZE || self.0 + steps < 0) {
             passed_zero += 1;
         }
+        // Starting on zero, the last full rotation lands on zero instead of passing it
+        if self.is_zero() && steps == 0 && passed_zero > 0 {
+            passed_zero -= 1;
+        }
 
         self.0 = (self.0 + steps).rem_euclid(DIAL_SIZE);
 
@@ -37,23 +43,48 @@ impl Dial {
     }
 }
 
-fn load_dial_changes(input: &str) -> Vec<i32> {
+fn load_dial_changes(input: &str) -> Result<Vec<i32>, ParseError> {
     input
         .lines()
-        .map(|line| {
-            let direction = line.chars().next().unwrap();
-            let value = line[1..].parse::<u32>().unwrap();
+        .enumerate()
+        .map(|(index, line)| {
+            let invalid = || {
+                ParseError::at_line(
+                    index + 1,
+                    format!("'{}' is not a rotation like L68 or R48", line),
+                )
+            };
+            let mut chars = line.chars();
+            let direction = chars.next().ok_or_else(invalid)?;
+            let value = chars
+                .as_str()
+                .parse::<u32>()
+                .ok()
+                .and_then(|value| i32::try_from(value).ok())
+                .ok_or_else(invalid)?;
             match direction {
-                'R' => value as i32,
-                'L' => -(value as i32),
-                _ => unreachable!("Invalid direction character"),
+                'R' => Ok(value),
+                'L' => Ok(-value),
+                _ => Err(invalid()),
             }
         })
         .collect()
 }
 
 pub fn solve_part1(input: &str) -> u32 {
-    let instructions = load_dial_changes(input);
+    try_solve_part1(input).unwrap()
+}
+
+pub fn solve_part2(input: &str) -> u32 {
+    try_solve_part2(input).unwrap()
+}
+
+/// Counts how often the dial stops at zero.
+///
+/// # Errors
+/// Fails if a line is not a rotation.
+pub fn try_solve_part1(input: &str) -> Result<u32, ParseError> {
+    let instructions = load_dial_changes(input)?;
     let mut dial = Dial::new();
 
     let zero_count = instructions.iter().fold(0, |acc: u32, instr| {
@@ -61,33 +92,50 @@ pub fn solve_part1(input: &str) -> u32 {
         acc + if dial.is_zero() { 1 } else { 0 }
     });
 
-    zero_count
+    Ok(zero_count)
 }
 
-pub fn solve_part2(input: &str) -> u32 {
-    let instructions = load_dial_changes(input);
+/// Counts how often the dial points at zero, including while it is turning.
+///
+/// # Errors
+/// Fails if a line is not a rotation or the count doesn't fit into 32 bits.
+pub fn try_solve_part2(input: &str) -> Result<u32, ParseError> {
+    let instructions = load_dial_changes(input)?;
     let mut dial = Dial::new();
 
-    let (zero_count, passed_zero_count) =
-        instructions.iter().fold((0, 0), |acc: (u32, u32), instr| {
-            let passed = dial.move_dial(*instr);
-            let zero_hits = if dial.is_zero() { 1 } else { 0 };
-            (acc.0 + zero_hits, acc.1 + passed)
-        });
+    instructions.iter().try_fold(0u32, |count, instr| {
+        let passed = dial.move_dial(*instr);
+        let zero_hits = if dial.is_zero() { 1 } else { 0 };
+        count
+            .checked_add(passed + zero_hits)
+            .ok_or_else(|| ParseError::new("The number of times the dial points at zero overflows"))
+    })
+}
 
-    passed_zero_count + zero_count
+/// Turns the dial one click at a time, kept as a reference for differential tests.
+pub fn solve_part2_with_clicks(input: &str) -> u32 {
+    let mut position = 50;
+    let mut zero_count = 0;
+    for steps in load_dial_changes(input).unwrap() {
+        for _ in 0..steps.abs() {
+            position = (position + steps.signum()).rem_euclid(DIAL_SIZE);
+            if position == 0 {
+                zero_count += 1;
+            }
+        }
+    }
+    zero_count
 }
 
 #[cfg(test)]
 mod tests {
-    use std::fs::read_to_string;
-
     use super::*;
+    use crate::fixture_or_skip;
 
     #[test]
     fn test_load_file() {
-        let input = read_to_string("input/day1/test_input.txt").unwrap();
-        let instructions = load_dial_changes(input.as_str());
+        let input = fixture_or_skip!(1, "test_input.txt");
+        let instructions = load_dial_changes(input.as_str()).unwrap();
         assert_eq!(instructions.len(), 4);
         assert_eq!(instructions[0], 10);
         assert_eq!(instructions[1], -20);
@@ -97,8 +145,8 @@ mod tests {
 
     #[test]
     fn test_example() {
-        let input = read_to_string("input/day1/example.txt").unwrap();
-        let instructions = load_dial_changes(input.as_str());
+        let input = fixture_or_skip!(1);
+        let instructions = load_dial_changes(input.as_str()).unwrap();
         let mut dial = Dial::new();
 
         let (zero_count, passed_zero_count) =
@@ -113,4 +161,33 @@ mod tests {
         assert_eq!(passed_zero_count, 3);
         assert_eq!(zero_count + passed_zero_count, 6);
     }
+
+    #[test]
+    fn test_move_dial_full_rotations_from_zero() {
+        let mut dial = Dial(0);
+        assert_eq!(dial.move_dial(100), 0);
+        assert!(dial.is_zero());
+        assert_eq!(dial.move_dial(-200), 1);
+        assert!(dial.is_zero());
+        assert_eq!(dial.move_dial(0), 0);
+        assert!(dial.is_zero());
+
+        let mut dial = Dial::new();
+        assert_eq!(dial.move_dial(150), 1);
+        assert!(dial.is_zero());
+    }
+
+    #[test]
+    fn test_invalid_rotations() {
+        for (input, line) in [
+            ("R10\nX5", 2),
+            ("L", 1),
+            ("R10\n\nL5", 2),
+            ("R-5", 1),
+            ("L3000000000", 1),
+        ] {
+            assert_eq!(try_solve_part1(input).unwrap_err().line, Some(line));
+        }
+        assert_eq!(try_solve_part2("R1000\nL50").unwrap(), 11);
+    }
 }
@@ -1,21 +1,46 @@
+use std::collections::BTreeMap;
+
 use itertools::Itertools;
 
-fn load_ranges(input: &str) -> Vec<(u128, u128)> {
+use crate::ParseError;
+
+/// Parses the comma separated ID ranges.
+///
+/// # Errors
+/// Fails if an item is not a range like `11-22` or its start is greater than its end.
+pub fn load_ranges(input: &str) -> Result<Vec<(u128, u128)>, ParseError> {
     input
+        .trim()
         .split(',')
-        .map(|range| {
-            range
+        .enumerate()
+        .map(|(index, range)| {
+            let (start, end) = range
                 .split('-')
-                .map(|num| num.parse::<u128>().expect("Failed to parse number"))
+                .map(|num| num.parse::<u128>().ok())
                 .collect_tuple()
-                .expect("Failed to collect tuple")
+                .and_then(|(start, end)| start.zip(end))
+                .ok_or_else(|| {
+                    ParseError::new(format!(
+                        "Range {}: '{}' is not a range like 11-22",
+                        index + 1,
+                        range
+                    ))
+                })?;
+            if start > end {
+                return Err(ParseError::new(format!(
+                    "Range {}: '{}' is reversed, its start is greater than its end",
+                    index + 1,
+                    range
+                )));
+            }
+            Ok((start, end))
         })
         .collect()
 }
 
 fn is_valid_id_part1(id: u128) -> bool {
     let id_len = id.to_string().len();
-    if id_len < 2 || id_len % 2 != 0 {
+    if id_len < 2 || !id_len.is_multiple_of(2) {
         return true;
     }
 
@@ -36,7 +61,7 @@ fn is_valid_id_part2(id: u128) -> bool {
     }
 
     for pat_len in 1..=(id_len / 2) {
-        if id_len % pat_len != 0 {
+        if !id_len.is_multiple_of(pat_len) {
             continue;
         }
         let pattern = &id_str[..pat_len];
@@ -47,70 +72,133 @@ fn is_valid_id_part2(id: u128) -> bool {
     true
 }
 
-fn sum_invalid_ids_in_ranges(ranges: &Vec<(u128, u128)>, is_valid_id: fn(u128) -> bool) -> u128 {
-    let mut sum = 0;
-    for (start, end) in ranges {
-        for id in *start..=*end {
+/// Sums all invalid IDs covered by the ranges.
+/// An ID covered by several ranges is counted once for each of them.
+fn sum_invalid_ids_in_ranges(
+    ranges: &[(u128, u128)],
+    is_valid_id: fn(u128) -> bool,
+) -> Result<u128, ParseError> {
+    let mut sum: u128 = 0;
+    for &(start, end) in ranges {
+        for id in start..=end {
             if !is_valid_id(id) {
-                sum += id;
+                sum = sum.checked_add(id).ok_or_else(sum_overflows)?;
             }
         }
     }
-    sum
+    Ok(sum)
+}
+
+fn sum_overflows() -> ParseError {
+    ParseError::new("The sum of the invalid IDs overflows")
 }
 
 pub fn solve_part1(input: &str) -> u128 {
-    let ranges = load_ranges(input);
-    sum_invalid_ids_in_ranges(&ranges, is_valid_id_part1)
+    try_solve_part1(input).unwrap()
 }
 
 pub fn solve_part2(input: &str) -> u128 {
-    let ranges = load_ranges(input);
-    sum_invalid_ids_in_ranges(&ranges, is_valid_id_part2)
+    try_solve_part2(input).unwrap()
+}
+
+/// Sums the IDs made of a sequence of digits repeated twice.
+///
+/// # Errors
+/// Fails if the ranges can't be parsed or the sum overflows.
+pub fn try_solve_part1(input: &str) -> Result<u128, ParseError> {
+    sum_invalid_ids_in_ranges(&load_ranges(input)?, is_valid_id_part1)
+}
+
+/// Sums the IDs made of a sequence of digits repeated at least twice.
+///
+/// # Errors
+/// Fails if the ranges can't be parsed or the sum overflows.
+pub fn try_solve_part2(input: &str) -> Result<u128, ParseError> {
+    sum_invalid_ids_in_ranges(&load_ranges(input)?, is_valid_id_part2)
+}
+
+/// Counts how many ranges cover every ID instead of summing range by range, kept as a reference for differential tests.
+pub fn solve_part1_with_id_counts(input: &str) -> u128 {
+    sum_invalid_ids_by_count(&load_ranges(input).unwrap(), is_valid_id_part1)
+}
+
+/// Counts how many ranges cover every ID instead of summing range by range, kept as a reference for differential tests.
+pub fn solve_part2_with_id_counts(input: &str) -> u128 {
+    sum_invalid_ids_by_count(&load_ranges(input).unwrap(), is_valid_id_part2)
+}
+
+fn sum_invalid_ids_by_count(ranges: &[(u128, u128)], is_valid_id: fn(u128) -> bool) -> u128 {
+    let mut counts: BTreeMap<u128, u128> = BTreeMap::new();
+    for id in ranges.iter().flat_map(|&(start, end)| start..=end) {
+        *counts.entry(id).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .filter(|&(id, _)| !is_valid_id(id))
+        .map(|(id, count)| id * count)
+        .sum()
 }
 
 #[cfg(test)]
 mod tests {
-    use std::fs::read_to_string;
-
     use super::*;
+    use crate::fixture_or_skip;
 
     #[test]
     fn test_load_ranges() {
-        let input = read_to_string("input/day2/example.txt").unwrap();
-        let ranges = load_ranges(input.as_str());
+        let input = fixture_or_skip!(2);
+        let ranges = load_ranges(input.as_str()).unwrap();
         assert_eq!(ranges.len(), 11);
     }
 
     #[test]
     fn test_is_valid_id_part1() {
-        assert_eq!(is_valid_id_part1(11), false);
-        assert_eq!(is_valid_id_part1(12), true);
-        assert_eq!(is_valid_id_part1(1234), true);
-        assert_eq!(is_valid_id_part1(1212), false);
-        assert_eq!(is_valid_id_part1(123456), true);
-        assert_eq!(is_valid_id_part1(123123), false);
-        assert_eq!(is_valid_id_part1(1), true);
+        assert!(!is_valid_id_part1(11));
+        assert!(is_valid_id_part1(12));
+        assert!(is_valid_id_part1(1234));
+        assert!(!is_valid_id_part1(1212));
+        assert!(is_valid_id_part1(123456));
+        assert!(!is_valid_id_part1(123123));
+        assert!(is_valid_id_part1(1));
     }
 
     #[test]
     fn test_is_valid_id_part2() {
-        assert_eq!(is_valid_id_part2(11), false);
-        assert_eq!(is_valid_id_part2(12), true);
-        assert_eq!(is_valid_id_part2(1234), true);
-        assert_eq!(is_valid_id_part2(1212), false);
-        assert_eq!(is_valid_id_part2(123456), true);
-        assert_eq!(is_valid_id_part2(123123), false);
-        assert_eq!(is_valid_id_part2(1), true);
+        assert!(!is_valid_id_part2(11));
+        assert!(is_valid_id_part2(12));
+        assert!(is_valid_id_part2(1234));
+        assert!(!is_valid_id_part2(1212));
+        assert!(is_valid_id_part2(123456));
+        assert!(!is_valid_id_part2(123123));
+        assert!(is_valid_id_part2(1));
     }
 
     #[test]
     fn test_example() {
-        let input = read_to_string("input/day2/example.txt").unwrap();
-        let ranges = load_ranges(input.as_str());
-        let sum = sum_invalid_ids_in_ranges(&ranges, is_valid_id_part1);
+        let input = fixture_or_skip!(2);
+        let ranges = load_ranges(input.as_str()).unwrap();
+        let sum = sum_invalid_ids_in_ranges(&ranges, is_valid_id_part1).unwrap();
         assert_eq!(sum, 1227775554); // result for part 1
-        let sum = sum_invalid_ids_in_ranges(&ranges, is_valid_id_part2);
+        let sum = sum_invalid_ids_in_ranges(&ranges, is_valid_id_part2).unwrap();
         assert_eq!(sum, 4174379265); // result for part 2
     }
+
+    #[test]
+    fn test_invalid_ranges() {
+        assert!(load_ranges("11-22,95-115\n").is_ok());
+        for input in ["11-22,", "11", "1-2-3", "a-b", "22-11", "-5-7"] {
+            assert!(try_solve_part1(input).is_err(), "{}", input);
+        }
+        let huge = format!("{}-{}", u128::MAX - 1, u128::MAX);
+        assert!(try_solve_part2(&format!("{},{}", huge, "11-11")).is_ok());
+    }
+
+    #[test]
+    fn test_overlapping_ranges_count_ids_per_range() {
+        assert_eq!(solve_part1("11-22,11-22"), 66);
+        assert_eq!(solve_part1("10-30,20-25"), 55);
+        assert_eq!(solve_part2("99-111,100-120"), 321);
+        assert_eq!(solve_part1_with_id_counts("11-22,11-22"), 66);
+        assert_eq!(solve_part2_with_id_counts("99-111,100-120"), 321);
+    }
 }
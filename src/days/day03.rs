@@ -1,18 +1,50 @@
-pub fn solve_part1(input: &str) -> u64 {
-    let battery_stacks = load_battery_stacks(input);
+use crate::ParseError;
 
-    battery_stacks
-        .iter()
-        .map(|batteries| max_battery_joltage(batteries, 2))
-        .sum()
+pub fn solve_part1(input: &str) -> u64 {
+    try_solve_part1(input).unwrap()
 }
 
 pub fn solve_part2(input: &str) -> u64 {
-    let battery_stacks = load_battery_stacks(input);
+    try_solve_part2(input).unwrap()
+}
+
+/// Sums the largest joltage of two batteries from each bank.
+///
+/// # Errors
+/// Fails if a battery is not a digit.
+pub fn try_solve_part1(input: &str) -> Result<u64, ParseError> {
+    total_joltage(input, 2)
+}
+
+/// Sums the largest joltage of twelve batteries from each bank.
+///
+/// # Errors
+/// Fails if a battery is not a digit.
+pub fn try_solve_part2(input: &str) -> Result<u64, ParseError> {
+    total_joltage(input, 12)
+}
+
+fn total_joltage(input: &str, keep: usize) -> Result<u64, ParseError> {
+    let battery_stacks = load_battery_stacks(input)?;
+
+    Ok(battery_stacks
+        .iter()
+        .map(|batteries| max_battery_joltage(batteries, keep))
+        .sum())
+}
 
-    battery_stacks
+/// Tries every pair of batteries, kept as a reference for differential tests.
+pub fn solve_part1_with_brute_force(input: &str) -> u64 {
+    load_battery_stacks(input)
+        .unwrap()
         .iter()
-        .map(|batteries| max_battery_joltage(batteries, 12))
+        .map(|batteries| {
+            (0..batteries.len())
+                .flat_map(|i| (i + 1..batteries.len()).map(move |j| (i, j)))
+                .map(|(i, j)| u64::from(batteries[i] * 10 + batteries[j]))
+                .max()
+                .unwrap_or(0)
+        })
         .sum()
 }
 
@@ -22,7 +54,7 @@ pub fn solve_part2(input: &str) -> u64 {
 /// * `batteries` - A vector of battery joltage ratings (1-9)
 /// * `keep` - The amount of batteries that should be combined for maximum joltage
 ///
-fn max_battery_joltage(batteries: &Vec<u8>, keep: usize) -> u64 {
+fn max_battery_joltage(batteries: &[u8], keep: usize) -> u64 {
     let mut stack: Vec<u8> = Vec::new();
     let batteries_len = batteries.len();
 
@@ -40,15 +72,20 @@ fn max_battery_joltage(batteries: &Vec<u8>, keep: usize) -> u64 {
     }
     stack
         .iter()
-        .fold(0 as u64, |acc, &digit| acc * 10 + digit as u64)
+        .fold(0u64, |acc, &digit| acc * 10 + digit as u64)
 }
 
-fn load_battery_stacks(input: &str) -> Vec<Vec<u8>> {
+fn load_battery_stacks(input: &str) -> Result<Vec<Vec<u8>>, ParseError> {
     input
         .lines()
-        .map(|line| {
+        .enumerate()
+        .map(|(index, line)| {
             line.chars()
-                .map(|c| c.to_digit(10).unwrap() as u8)
+                .map(|c| {
+                    c.to_digit(10).map(|digit| digit as u8).ok_or_else(|| {
+                        ParseError::at_line(index + 1, format!("'{}' is not a battery joltage", c))
+                    })
+                })
                 .collect()
         })
         .collect()
@@ -56,14 +93,13 @@ fn load_battery_stacks(input: &str) -> Vec<Vec<u8>> {
 
 #[cfg(test)]
 mod tests {
-    use std::fs::read_to_string;
-
     use super::*;
+    use crate::fixture_or_skip;
 
     #[test]
     fn test_load_battery_stacks() {
         let input = "123\n456\n789";
-        let stacks = load_battery_stacks(input);
+        let stacks = load_battery_stacks(input).unwrap();
         assert_eq!(stacks.len(), 3);
         assert_eq!(stacks[0], vec![1, 2, 3]);
         assert_eq!(stacks[1], vec![4, 5, 6]);
@@ -72,15 +108,21 @@ mod tests {
 
     #[test]
     fn test_example_part1() {
-        let input = read_to_string("input/day03/example.txt").unwrap();
+        let input = fixture_or_skip!(3);
         let result = solve_part1(&input);
         assert_eq!(result, 357);
     }
 
     #[test]
     fn test_example_part2() {
-        let input = read_to_string("input/day03/example.txt").unwrap();
+        let input = fixture_or_skip!(3);
         let result = solve_part2(&input);
         assert_eq!(result, 3121910778619);
     }
+
+    #[test]
+    fn test_invalid_batteries() {
+        assert_eq!(try_solve_part1("12\n3x").unwrap_err().line, Some(2));
+        assert_eq!(try_solve_part2("987").unwrap(), 987);
+    }
 }
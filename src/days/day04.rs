@@ -1,5 +1,7 @@
 use std::error::Error;
 
+use crate::ParseError;
+
 #[derive(Debug, Clone)]
 struct Grid {
     data: Vec<u8>,
@@ -28,6 +30,134 @@ impl Grid {
     }
 }
 
+/// Common interface of the grid representations used to simulate the roll removal.
+trait RollGrid {
+    /// Removes all rolls with less than four neighboring rolls at once.
+    ///
+    /// # Returns
+    /// The number of rolls that were removed.
+    fn remove_accessible_rolls(&mut self) -> usize;
+}
+
+impl RollGrid for Grid {
+    fn remove_accessible_rolls(&mut self) -> usize {
+        calculate_removable_rolls(self, &NEIGHBOR_SUM)
+    }
+}
+
+/// A grid that packs each row into 64 bit words, one bit per cell.
+///
+/// Bit `i` of word `w` in a row holds the cell at `x = w * 64 + i`.
+/// Bits beyond the grid width are always zero, so they never count as neighbors.
+#[derive(Debug, Clone)]
+struct BitGrid {
+    rows: Vec<u64>,
+    words_per_row: usize,
+    height: usize,
+}
+
+impl BitGrid {
+    fn new(width: usize, height: usize) -> Self {
+        let words_per_row = width.div_ceil(64);
+        Self {
+            rows: vec![0; words_per_row * height],
+            words_per_row,
+            height,
+        }
+    }
+
+    fn row(&self, y: usize) -> &[u64] {
+        &self.rows[y * self.words_per_row..(y + 1) * self.words_per_row]
+    }
+
+    fn set(&mut self, x: usize, y: usize, value: bool) {
+        let word = &mut self.rows[y * self.words_per_row + x / 64];
+        if value {
+            *word |= 1 << (x % 64);
+        } else {
+            *word &= !(1 << (x % 64));
+        }
+    }
+
+    /// Computes the mask of rolls in row `y` with less than four neighboring rolls.
+    ///
+    /// The eight neighbors are shifted onto the cell positions and summed with a bit-sliced
+    /// counter, so all 64 cells of a word are counted at once.
+    ///
+    /// Rows outside of the grid are read from `empty`.
+    fn accessible_rolls_in_row(&self, y: usize, empty: &[u64], mask: &mut [u64]) {
+        let above = if y > 0 { self.row(y - 1) } else { empty };
+        let below = if y + 1 < self.height {
+            self.row(y + 1)
+        } else {
+            empty
+        };
+        let current = self.row(y);
+
+        for (w, accessible) in mask.iter_mut().enumerate() {
+            // Counts up to three neighbors in ones and twos, fours marks four or more neighbors
+            let (mut ones, mut twos, mut fours) = (0u64, 0u64, 0u64);
+            let mut add = |bits: u64| {
+                let carry = ones & bits;
+                ones ^= bits;
+                fours |= twos & carry;
+                twos ^= carry;
+            };
+
+            for (row, include_center) in [(above, true), (current, false), (below, true)] {
+                add(shift_from_left(row, w));
+                add(shift_from_right(row, w));
+                if include_center {
+                    add(row[w]);
+                }
+            }
+
+            *accessible = current[w] & !fours;
+        }
+    }
+}
+
+/// Moves the cell at `x - 1` onto position `x` for word `w` of a packed row.
+fn shift_from_left(row: &[u64], w: usize) -> u64 {
+    let carry = if w > 0 { row[w - 1] >> 63 } else { 0 };
+    (row[w] << 1) | carry
+}
+
+/// Moves the cell at `x + 1` onto position `x` for word `w` of a packed row.
+fn shift_from_right(row: &[u64], w: usize) -> u64 {
+    let carry = if w + 1 < row.len() {
+        row[w + 1] << 63
+    } else {
+        0
+    };
+    (row[w] >> 1) | carry
+}
+
+impl RollGrid for BitGrid {
+    fn remove_accessible_rolls(&mut self) -> usize {
+        // Rows without cells have no words to chunk the mask into
+        if self.words_per_row == 0 {
+            return 0;
+        }
+        let empty = vec![0; self.words_per_row];
+        let mut to_remove = vec![0; self.rows.len()];
+
+        for (y, mask) in to_remove.chunks_mut(self.words_per_row).enumerate() {
+            self.accessible_rolls_in_row(y, &empty, mask);
+        }
+
+        self.rows
+            .iter_mut()
+            .zip(&to_remove)
+            .for_each(|(word, remove)| *word &= !remove);
+
+        to_remove
+            .iter()
+            .map(|mask| mask.count_ones() as usize)
+            .sum()
+    }
+}
+
 type Kernel3x3<T> = [[T; 3]; 3];
 const NEIGHBOR_SUM: Kernel3x3<i8> = [[1, 1, 1], [1, 0, 1], [1, 1, 1]];
 
@@ -42,8 +172,8 @@ fn calculate_removable_rolls(grid: &mut Grid, kernel: &Kernel3x3<i8>) -> usize {
             }
 
             let mut sum: i8 = 0;
-            for ky in 0..3 {
-                for kx in 0..3 {
+            for (ky, kernel_row) in kernel.iter().enumerate() {
+                for (kx, weight) in kernel_row.iter().enumerate() {
                     let offset_x = x as isize + kx as isize - 1;
                     let offset_y = y as isize + ky as isize - 1;
 
@@ -56,7 +186,7 @@ fn calculate_removable_rolls(grid: &mut Grid, kernel: &Kernel3x3<i8>) -> usize {
                     }
 
                     let value = grid.get(offset_x as usize, offset_y as usize);
-                    sum += kernel[ky][kx] * (*value as i8);
+                    sum += weight * (*value as i8);
                 }
             }
             if sum < 4 {
@@ -73,33 +203,74 @@ fn calculate_removable_rolls(grid: &mut Grid, kernel: &Kernel3x3<i8>) -> usize {
     removable_rolls
 }
 
-fn load_grid_from_str(input: &str) -> Result<Grid, Box<dyn Error>> {
-    let lines: Vec<&str> = input.lines().collect();
-    let height = lines.len();
-    let width = lines[0].chars().count();
+/// Reads the rows of the grid, with `true` for every roll of paper.
+///
+/// # Errors
+/// Fails if the grid is empty, a row is longer or shorter than the first one,
+/// or a cell is neither a roll `@` nor empty `.`.
+fn load_rows(input: &str) -> Result<Vec<Vec<bool>>, ParseError> {
+    let rows = input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| {
+            line.chars()
+                .map(|c| match c {
+                    '@' => Ok(true),
+                    '.' => Ok(false),
+                    _ => Err(ParseError::at_line(
+                        index + 1,
+                        format!("'{}' is neither a roll of paper '@' nor empty '.'", c),
+                    )),
+                })
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
 
-    Grid::new(
-        width,
-        height,
-        lines
-            .iter()
-            .flat_map(|line| line.chars().map(|c| if c == '@' { 1 } else { 0 }))
-            .collect(),
-    )
+    let width = rows
+        .first()
+        .map(Vec::len)
+        .ok_or_else(|| ParseError::new("The grid is empty"))?;
+    if let Some((index, row)) = rows.iter().enumerate().find(|(_, row)| row.len() != width) {
+        return Err(ParseError::at_line(
+            index + 1,
+            format!(
+                "Expected {} cells like the first row but found {}",
+                width,
+                row.len()
+            ),
+        ));
+    }
+    Ok(rows)
 }
 
-pub fn solve_part1(input: &str) -> usize {
-    let mut grid = load_grid_from_str(input).unwrap();
+fn load_grid_from_str(input: &str) -> Result<Grid, ParseError> {
+    let rows = load_rows(input)?;
 
-    calculate_removable_rolls(&mut grid, &NEIGHBOR_SUM)
+    Ok(Grid::new(
+        rows[0].len(),
+        rows.len(),
+        rows.into_iter().flatten().map(u8::from).collect(),
+    )?)
 }
 
-pub fn solve_part2(_input: &str) -> usize {
-    let mut grid = load_grid_from_str(_input).unwrap();
+fn load_bit_grid_from_str(input: &str) -> Result<BitGrid, ParseError> {
+    let rows = load_rows(input)?;
+    let mut grid = BitGrid::new(rows[0].len(), rows.len());
+
+    for (y, row) in rows.iter().enumerate() {
+        for (x, &roll) in row.iter().enumerate() {
+            grid.set(x, y, roll);
+        }
+    }
+
+    Ok(grid)
+}
+
+fn remove_all_accessible_rolls(grid: &mut impl RollGrid) -> usize {
     let mut total_removable = 0;
 
     loop {
-        let removable = calculate_removable_rolls(&mut grid, &NEIGHBOR_SUM);
+        let removable = grid.remove_accessible_rolls();
 
         if removable == 0 {
             break;
@@ -109,11 +280,52 @@ pub fn solve_part2(_input: &str) -> usize {
     total_removable
 }
 
+pub fn solve_part1(input: &str) -> usize {
+    try_solve_part1(input).unwrap()
+}
+
+pub fn solve_part2(input: &str) -> usize {
+    try_solve_part2(input).unwrap()
+}
+
+/// Counts the rolls of paper that can be removed at once.
+///
+/// # Errors
+/// Fails if the input is not a rectangular grid of `@` and `.`.
+pub fn try_solve_part1(input: &str) -> Result<usize, ParseError> {
+    let mut grid = load_grid_from_str(input)?;
+
+    Ok(grid.remove_accessible_rolls())
+}
+
+/// Counts the rolls of paper that can be removed until none are accessible.
+///
+/// # Errors
+/// Fails if the input is not a rectangular grid of `@` and `.`.
+pub fn try_solve_part2(input: &str) -> Result<usize, ParseError> {
+    let mut grid = load_grid_from_str(input)?;
+
+    Ok(remove_all_accessible_rolls(&mut grid))
+}
+
+pub fn solve_part1_with_bitset(input: &str) -> usize {
+    let mut grid = load_bit_grid_from_str(input).unwrap();
+
+    grid.remove_accessible_rolls()
+}
+
+pub fn solve_part2_with_bitset(input: &str) -> usize {
+    let mut grid = load_bit_grid_from_str(input).unwrap();
+
+    remove_all_accessible_rolls(&mut grid)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::fixture_or_skip;
 
-    use std::{fs::read_to_string, vec};
+    use std::vec;
 
     #[test]
     fn test_grid_get_set() {
@@ -131,17 +343,92 @@ mod tests {
         assert_eq!(grid.data, vec![1, 0, 0, 0, 0, 1, 0, 1, 0]);
     }
 
+    #[test]
+    fn test_bit_grid_set() {
+        let mut grid = BitGrid::new(130, 2);
+        grid.set(63, 0, true);
+        grid.set(64, 1, true);
+        grid.set(129, 1, true);
+        grid.set(129, 1, false);
+        assert_eq!(grid.words_per_row, 3);
+        assert_eq!(grid.rows, vec![1 << 63, 0, 0, 0, 1, 0]);
+    }
+
+    #[test]
+    fn test_bit_grid_matches_grid_across_word_boundaries() {
+        let input = (0..7)
+            .map(|y| {
+                (0..150)
+                    .map(|x| if (x * 7 + y * 3) % 5 < 3 { '@' } else { '.' })
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+        let mut grid = load_grid_from_str(&input).unwrap();
+        let mut bit_grid = load_bit_grid_from_str(&input).unwrap();
+
+        loop {
+            let removed = grid.remove_accessible_rolls();
+            assert_eq!(bit_grid.remove_accessible_rolls(), removed);
+
+            let mut expected = BitGrid::new(grid.width, grid.height);
+            for y in 0..grid.height {
+                for x in 0..grid.width {
+                    expected.set(x, y, *grid.get(x, y) == 1);
+                }
+            }
+            assert_eq!(bit_grid.rows, expected.rows);
+
+            if removed == 0 {
+                break;
+            }
+        }
+    }
+
     #[test]
     fn test_solve_part1() {
-        let input = read_to_string("input/day04/example.txt").unwrap();
+        let input = fixture_or_skip!(4);
         let result = solve_part1(&input);
         assert_eq!(result, 13);
     }
 
     #[test]
     fn test_solve_part2() {
-        let input = read_to_string("input/day04/example.txt").unwrap();
+        let input = fixture_or_skip!(4);
         let result = solve_part2(&input);
         assert_eq!(result, 43);
     }
+
+    #[test]
+    fn test_solve_part1_with_bitset() {
+        let input = fixture_or_skip!(4);
+        let result = solve_part1_with_bitset(&input);
+        assert_eq!(result, 13);
+    }
+
+    #[test]
+    fn test_solve_part2_with_bitset() {
+        let input = fixture_or_skip!(4);
+        let result = solve_part2_with_bitset(&input);
+        assert_eq!(result, 43);
+    }
+
+    #[test]
+    fn test_invalid_grids() {
+        assert_eq!(
+            try_solve_part1("").unwrap_err(),
+            ParseError::new("The grid is empty")
+        );
+        assert_eq!(try_solve_part1("@@.\n@.").unwrap_err().line, Some(2));
+        assert_eq!(try_solve_part2("@@.\n@#.").unwrap_err().line, Some(2));
+        assert!(load_bit_grid_from_str("@.\n.@\n@").is_err());
+    }
+
+    #[test]
+    fn test_grid_without_columns() {
+        assert_eq!(solve_part1("\n"), 0);
+        assert_eq!(solve_part2("\n\n"), 0);
+        assert_eq!(solve_part1_with_bitset("\n"), 0);
+        assert_eq!(solve_part2_with_bitset("\n\n"), 0);
+    }
 }
@@ -1,90 +1,352 @@
+use std::{error::Error, fmt::Display, io::BufRead, str::FromStr};
+
+use crate::{IntervalBound, IntervalSet, ParseError};
+
 pub fn solve_part1(input: &str) -> usize {
-    let (ranges, ids) = load_ranges_and_ids(input);
-    ids.into_iter()
-        .filter(|id| ranges.iter().any(|range| range.contains(*id)))
-        .count()
+    try_solve_part1(input).unwrap()
 }
 
-pub fn solve_part2(input: &str) -> usize {
-    let (ranges, _ids) = load_ranges_and_ids(input);
+pub fn solve_part2(input: &str) -> u64 {
+    try_solve_part2(input).unwrap()
+}
 
-    // Combine overlapping ranges
-    let mut merged_ranges: Vec<Range> = Vec::new();
-    let mut sorted_ranges = ranges.clone();
-    sorted_ranges.sort_by_key(|range| range.start);
+/// Counts the available IDs that are fresh, see [`count_fresh_ids`].
+///
+/// # Errors
+/// Fails if the input is malformed.
+pub fn try_solve_part1(input: &str) -> Result<usize, ParseError> {
+    Ok(count_fresh_ids::<u64>(input)?)
+}
 
-    for range in sorted_ranges {
-        if let Some(last) = merged_ranges.last_mut() {
-            if range.start <= last.end {
-                last.end = last.end.max(range.end);
-            } else {
-                merged_ranges.push(range);
+/// Counts all fresh IDs, see [`count_fresh_ids_in_ranges`].
+///
+/// # Errors
+/// Fails if the input is malformed or the count doesn't fit into 64 bits.
+pub fn try_solve_part2(input: &str) -> Result<u64, ParseError> {
+    Ok(count_fresh_ids_in_ranges::<u64>(input)?)
+}
+
+/// Checks every ID against every range, kept as a reference for differential tests.
+pub fn solve_part1_with_linear_scan(input: &str) -> usize {
+    let (ranges, ids) = load_ranges_and_ids::<u64>(input).unwrap();
+
+    ids.iter()
+        .filter(|&&id| {
+            ranges
+                .iter()
+                .any(|range| range.start <= id && id <= range.end)
+        })
+        .count()
+}
+
+/// Counts the IDs by sorting and merging the ranges, kept as a reference for differential tests.
+pub fn solve_part2_with_sorted_ranges(input: &str) -> u64 {
+    let (mut ranges, _ids) = load_ranges_and_ids::<u64>(input).unwrap();
+    ranges.sort_unstable_by_key(|range| range.start);
+
+    let mut count = 0;
+    let mut merged: Option<(u64, u64)> = None;
+    for range in ranges {
+        merged = match merged {
+            Some((start, end)) if range.start <= end.saturating_add(1) => {
+                Some((start, end.max(range.end)))
             }
-        } else {
-            merged_ranges.push(range);
+            Some((start, end)) => {
+                count += end - start + 1;
+                Some((range.start, range.end))
+            }
+            None => Some((range.start, range.end)),
+        };
+    }
+    if let Some((start, end)) = merged {
+        count += end - start + 1;
+    }
+    count
+}
+
+/// Integer widths the ingredient IDs can be parsed as.
+///
+/// 64 bit IDs are enough for the puzzle input, 128 bit IDs allow counting ranges that cover
+/// the whole 64 bit domain.
+pub trait IngredientId: IntervalBound + FromStr + Display {}
+
+impl IngredientId for u64 {}
+impl IngredientId for u128 {}
+
+/// Counts the available ingredient IDs that are contained in any fresh range.
+pub fn count_fresh_ids<T: IngredientId>(input: &str) -> Result<usize, Box<dyn Error>> {
+    count_fresh_ids_from_reader::<T>(input.as_bytes())
+}
+
+/// Counts the available ingredient IDs that are contained in any fresh range while reading the input.
+///
+/// Only the ranges are kept in memory, the IDs are checked one by one as they are read,
+/// so the ID section can be arbitrarily large.
+pub fn count_fresh_ids_from_reader<T: IngredientId>(
+    reader: impl BufRead,
+) -> Result<usize, Box<dyn Error>> {
+    let mut sections = SectionReader::new(reader);
+    let fresh_ids = ranges_to_interval_set(&sections.read_ranges::<T>()?);
+
+    let mut count = 0;
+    while let Some(id) = sections.next_id::<T>()? {
+        if fresh_ids.contains(id) {
+            count += 1;
         }
     }
+    Ok(count)
+}
+
+/// Counts all IDs that are contained in any fresh range.
+///
+/// # Errors
+/// Fails if the input is malformed or if the ranges cover every value of `T`,
+/// as that count does not fit into `T`. Use a wider `T` in that case.
+pub fn count_fresh_ids_in_ranges<T: IngredientId>(input: &str) -> Result<T, Box<dyn Error>> {
+    let (ranges, _ids) = load_ranges_and_ids::<T>(input)?;
+
+    ranges_to_interval_set(&ranges)
+        .covered_len()
+        .ok_or_else(|| "The ranges cover every ID, the count exceeds the ID width".into())
+}
 
-    merged_ranges
+fn ranges_to_interval_set<T: IngredientId>(ranges: &[Range<T>]) -> IntervalSet<T> {
+    ranges
         .iter()
-        .map(|range| range.end - range.start + 1)
-        .sum()
+        .map(|range| (range.start, range.end))
+        .collect()
 }
 
-#[derive(Debug, Clone)]
-struct Range {
-    start: usize,
-    end: usize,
+/// An inclusive range of fresh ingredient IDs, `start` is guaranteed to be at most `end`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Range<T> {
+    start: T,
+    end: T,
+    line: usize,
 }
 
-impl Range {
-    fn new(start: usize, end: usize) -> Self {
-        Range { start, end }
+impl<T: IngredientId> Range<T> {
+    fn new(start: T, end: T, line: usize) -> Result<Self, Box<dyn Error>> {
+        if start > end {
+            return Err(ParseError::at_line(
+                line,
+                format!("Range {start}-{end} is reversed, its start is greater than its end"),
+            )
+            .into());
+        }
+        Ok(Range { start, end, line })
+    }
+
+    pub fn start(&self) -> T {
+        self.start
+    }
+
+    pub fn end(&self) -> T {
+        self.end
+    }
+
+    /// The line of the input the range was read from, starting at 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    fn contains(&self, id: T) -> bool {
+        self.start <= id && id <= self.end
+    }
+}
+
+impl<T: IngredientId> Display for Range<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}-{}", self.line, self.start, self.end)
+    }
+}
+
+/// Explains why an ingredient ID is considered fresh or spoiled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdDiagnostic<T> {
+    pub id: T,
+    /// Every input range that contains the ID, in input order.
+    pub containing_ranges: Vec<Range<T>>,
+    /// The merged interval the ID falls into, if it is fresh.
+    pub merged_interval: Option<(T, T)>,
+    /// The range with the greatest end below the ID, only set for spoiled IDs.
+    pub nearest_below: Option<Range<T>>,
+    /// The range with the smallest start above the ID, only set for spoiled IDs.
+    pub nearest_above: Option<Range<T>>,
+}
+
+impl<T: IngredientId> IdDiagnostic<T> {
+    pub fn is_fresh(&self) -> bool {
+        self.merged_interval.is_some()
     }
+}
 
-    fn contains(&self, value: usize) -> bool {
-        value >= self.start && value <= self.end
+impl<T: IngredientId> Display for IdDiagnostic<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some((start, end)) = self.merged_interval {
+            writeln!(f, "ID {}: fresh", self.id)?;
+            for range in &self.containing_ranges {
+                writeln!(f, "  contained in {range}")?;
+            }
+            write!(f, "  merged interval: {start}-{end}")
+        } else {
+            writeln!(f, "ID {}: spoiled", self.id)?;
+            match &self.nearest_below {
+                Some(range) => writeln!(f, "  nearest range below: {range}")?,
+                None => writeln!(f, "  nearest range below: none")?,
+            }
+            match &self.nearest_above {
+                Some(range) => write!(f, "  nearest range above: {range}"),
+                None => write!(f, "  nearest range above: none"),
+            }
+        }
     }
 }
 
-fn load_ranges_and_ids(input: &str) -> (Vec<Range>, Vec<usize>) {
-    let mut ranges = Vec::new();
+/// Creates a diagnostic for every available ingredient ID in input order.
+pub fn diagnose_ids<T: IngredientId>(input: &str) -> Result<Vec<IdDiagnostic<T>>, Box<dyn Error>> {
+    let (ranges, ids) = load_ranges_and_ids::<T>(input)?;
+    let fresh_ids = ranges_to_interval_set(&ranges);
+
+    Ok(ids
+        .into_iter()
+        .map(|id| {
+            let merged_interval = fresh_ids.interval_containing(id);
+            let (nearest_below, nearest_above) = if merged_interval.is_some() {
+                (None, None)
+            } else {
+                (
+                    ranges
+                        .iter()
+                        .filter(|range| range.end < id)
+                        .max_by_key(|range| range.end)
+                        .cloned(),
+                    ranges
+                        .iter()
+                        .filter(|range| range.start > id)
+                        .min_by_key(|range| range.start)
+                        .cloned(),
+                )
+            };
+
+            IdDiagnostic {
+                id,
+                containing_ranges: ranges
+                    .iter()
+                    .filter(|range| range.contains(id))
+                    .cloned()
+                    .collect(),
+                merged_interval,
+                nearest_below,
+                nearest_above,
+            }
+        })
+        .collect())
+}
+
+fn parse_id<T: IngredientId>(value: &str, line_number: usize) -> Result<T, Box<dyn Error>> {
+    value.trim().parse::<T>().map_err(|_| {
+        ParseError::at_line(line_number, format!("'{value}' is not a valid ID")).into()
+    })
+}
+
+type RangesAndIds<T> = (Vec<Range<T>>, Vec<T>);
+
+fn load_ranges_and_ids<T: IngredientId>(input: &str) -> Result<RangesAndIds<T>, Box<dyn Error>> {
+    let mut sections = SectionReader::new(input.as_bytes());
+    let ranges = sections.read_ranges()?;
+
     let mut ids = Vec::new();
-    let mut is_ids = false;
+    while let Some(id) = sections.next_id()? {
+        ids.push(id);
+    }
+
+    Ok((ranges, ids))
+}
+
+/// Reads the range and the ID section of the input line by line.
+///
+/// The sections are separated by one or more blank lines, blank lines before the ranges
+/// and within the IDs are skipped. Both LF and CRLF line endings are accepted.
+struct SectionReader<R> {
+    reader: R,
+    line: String,
+    line_number: usize,
+}
 
-    for line in input.lines() {
-        if line.trim().is_empty() {
-            is_ids = true;
-            continue;
+impl<R: BufRead> SectionReader<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line: String::new(),
+            line_number: 0,
         }
+    }
 
-        if !is_ids {
-            let parts: Vec<usize> = line
-                .split('-')
-                .map(|num| num.parse::<usize>().expect("Failed to parse number"))
-                .collect::<Vec<usize>>();
-            let start = parts[0];
-            let end = parts[1];
-            ranges.push(Range::new(start, end));
-        } else {
-            let id = line.trim().parse::<usize>().expect("Failed to parse ID");
-            ids.push(id);
+    /// Reads the next line into `self.line` without its line ending.
+    ///
+    /// # Returns
+    /// `false` once the end of the input is reached.
+    fn read_line(&mut self) -> Result<bool, Box<dyn Error>> {
+        self.line.clear();
+        if self.reader.read_line(&mut self.line)? == 0 {
+            return Ok(false);
+        }
+        self.line_number += 1;
+
+        let content_len = self.line.trim_end_matches(['\n', '\r']).len();
+        self.line.truncate(content_len);
+        Ok(true)
+    }
+
+    /// Reads all ranges up to the first blank line that follows a range.
+    fn read_ranges<T: IngredientId>(&mut self) -> Result<Vec<Range<T>>, Box<dyn Error>> {
+        let mut ranges = Vec::new();
+
+        while self.read_line()? {
+            let line = self.line.trim();
+            if line.is_empty() {
+                if ranges.is_empty() {
+                    continue;
+                }
+                break;
+            }
+
+            let (start, end) = line.split_once('-').ok_or_else(|| {
+                ParseError::at_line(self.line_number, "Expected a range 'start-end'")
+            })?;
+            ranges.push(Range::new(
+                parse_id(start, self.line_number)?,
+                parse_id(end, self.line_number)?,
+                self.line_number,
+            )?);
         }
+
+        Ok(ranges)
     }
 
-    (ranges, ids)
+    /// Reads the next ID, skipping blank lines.
+    fn next_id<T: IngredientId>(&mut self) -> Result<Option<T>, Box<dyn Error>> {
+        while self.read_line()? {
+            if !self.line.trim().is_empty() {
+                return parse_id(&self.line, self.line_number).map(Some);
+            }
+        }
+        Ok(None)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::fixture_or_skip;
 
-    use std::fs::read_to_string;
+    use std::io::Read;
 
     #[test]
     fn test_load_ranges_and_ids() {
-        let input = read_to_string("input/day05/example.txt").unwrap();
-        let (ranges, ids) = load_ranges_and_ids(&input);
+        let input = fixture_or_skip!(5);
+        let (ranges, ids) = load_ranges_and_ids::<u64>(&input).unwrap();
 
         assert_eq!(ranges.len(), 4);
         assert_eq!(ranges[0].start, 3);
@@ -100,15 +362,137 @@ mod tests {
 
     #[test]
     fn test_solve_part1() {
-        let input = read_to_string("input/day05/example.txt").unwrap();
+        let input = fixture_or_skip!(5);
         let result = solve_part1(&input);
         assert_eq!(result, 3);
     }
 
     #[test]
     fn test_solve_part2() {
-        let input = read_to_string("input/day05/example.txt").unwrap();
+        let input = fixture_or_skip!(5);
         let result = solve_part2(&input);
         assert_eq!(result, 14);
     }
+
+    #[test]
+    fn test_reversed_range_is_rejected() {
+        let error = load_ranges_and_ids::<u64>("3-5\n8-6\n\n4").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Line 2: Range 8-6 is reversed, its start is greater than its end"
+        );
+    }
+
+    #[test]
+    fn test_malformed_lines_are_rejected() {
+        assert!(load_ranges_and_ids::<u64>("3\n\n4").is_err());
+        assert!(load_ranges_and_ids::<u64>("3-x\n\n4").is_err());
+        assert!(load_ranges_and_ids::<u64>("3-5\n\n-4").is_err());
+        assert_eq!(try_solve_part1("3-5\n8\n\n4").unwrap_err().line, Some(2));
+        assert_eq!(try_solve_part2("3-5\n8-6\n\n4").unwrap_err().line, Some(2));
+        assert_eq!(try_solve_part1("3-5\n\n4\nx").unwrap_err().line, Some(4));
+    }
+
+    #[test]
+    fn test_adjacent_nested_and_touching_ranges() {
+        let adjacent = "3-5\n6-8\n\n5\n6\n9";
+        assert_eq!(count_fresh_ids_in_ranges::<u64>(adjacent).unwrap(), 6);
+        assert_eq!(count_fresh_ids::<u64>(adjacent).unwrap(), 2);
+
+        let nested = "1-10\n3-5\n4-4\n\n4\n11";
+        assert_eq!(count_fresh_ids_in_ranges::<u64>(nested).unwrap(), 10);
+        assert_eq!(count_fresh_ids::<u64>(nested).unwrap(), 1);
+
+        let touching = "3-5\n5-8\n8-8\n\n8";
+        assert_eq!(count_fresh_ids_in_ranges::<u64>(touching).unwrap(), 6);
+        assert_eq!(count_fresh_ids::<u64>(touching).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_ranges_at_the_edge_of_the_domain() {
+        let max = u64::MAX;
+        let input = format!("{}-{max}\n0-1\n\n{max}\n2", max - 9);
+        assert_eq!(count_fresh_ids_in_ranges::<u64>(&input).unwrap(), 12);
+        assert_eq!(count_fresh_ids::<u64>(&input).unwrap(), 1);
+
+        let input = format!("0-{}\n{}-{max}\n", max / 2, max / 2 + 1);
+        assert!(count_fresh_ids_in_ranges::<u64>(&input).is_err());
+        assert_eq!(
+            count_fresh_ids_in_ranges::<u128>(&input).unwrap(),
+            1u128 << 64
+        );
+
+        let input = format!("{max}-{}\n", u128::MAX);
+        assert!(count_fresh_ids_in_ranges::<u64>(&input).is_err());
+        assert_eq!(
+            count_fresh_ids_in_ranges::<u128>(&input).unwrap(),
+            u128::MAX - max as u128 + 1
+        );
+    }
+
+    #[test]
+    fn test_diagnose_ids() {
+        let input = fixture_or_skip!(5);
+        let diagnostics = diagnose_ids::<u64>(&input).unwrap();
+
+        assert_eq!(diagnostics.len(), 6);
+        assert_eq!(
+            diagnostics.iter().filter(|d| d.is_fresh()).count(),
+            solve_part1(&input)
+        );
+
+        let fresh = &diagnostics[4];
+        assert_eq!(fresh.id, 17);
+        assert_eq!(
+            fresh
+                .containing_ranges
+                .iter()
+                .map(|range| range.line())
+                .collect::<Vec<_>>(),
+            vec![3, 4]
+        );
+        assert_eq!(fresh.merged_interval, Some((10, 20)));
+        assert_eq!(fresh.nearest_below, None);
+
+        let spoiled = &diagnostics[2];
+        assert_eq!(spoiled.id, 8);
+        assert!(spoiled.containing_ranges.is_empty());
+        assert_eq!(spoiled.nearest_below.as_ref().map(|r| r.line()), Some(1));
+        assert_eq!(spoiled.nearest_above.as_ref().map(|r| r.line()), Some(2));
+    }
+
+    #[test]
+    fn test_diagnostic_display() {
+        let diagnostics = diagnose_ids::<u64>("3-5\n4-8\n\n4\n10").unwrap();
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "ID 4: fresh\n  contained in line 1: 3-5\n  contained in line 2: 4-8\n  merged interval: 3-8"
+        );
+        assert_eq!(
+            diagnostics[1].to_string(),
+            "ID 10: spoiled\n  nearest range below: line 2: 4-8\n  nearest range above: none"
+        );
+    }
+
+    #[test]
+    fn test_blank_lines_and_crlf() {
+        let input = "\r\n3-5\r\n10-14\r\n\r\n\r\n1\r\n5\r\n\r\n11\r\n";
+        let (ranges, ids) = load_ranges_and_ids::<u64>(input).unwrap();
+        assert_eq!(ranges.len(), 2);
+        assert_eq!(ranges[1].line(), 3);
+        assert_eq!(ids, vec![1, 5, 11]);
+        assert_eq!(count_fresh_ids::<u64>(input).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_count_fresh_ids_from_reader() {
+        let ids = (0..10_000).map(|id| format!("{id}\n")).collect::<String>();
+        let reader = "3-5\n10-14\n16-20\n12-18\n\n"
+            .as_bytes()
+            .chain(ids.as_bytes());
+        assert_eq!(count_fresh_ids_from_reader::<u64>(reader).unwrap(), 14);
+
+        let error = count_fresh_ids_from_reader::<u64>("3-5\n\n4\nx\n".as_bytes()).unwrap_err();
+        assert_eq!(error.to_string(), "Line 4: 'x' is not a valid ID");
+    }
 }
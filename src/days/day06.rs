@@ -1,196 +1,880 @@
+use std::{error::Error, fmt::Display, ops::Range, str::FromStr};
+
 use itertools::Itertools;
 use transpose::transpose;
 
+use crate::ParseError;
+
 pub fn solve_part1(input: &str) -> usize {
-    let line_count = input.lines().count();
-    let ops: Vec<&str> = input.lines().last().unwrap().split_whitespace().collect();
-    let mut results = vec![0; ops.len()];
+    try_solve_part1(input).unwrap()
+}
 
-    for (i, line) in input.lines().enumerate() {
-        if i == line_count - 1 {
-            break;
-        }
-        for (j, num_str) in line.split_whitespace().enumerate() {
-            match ops[j] {
-                "+" => {
-                    results[j] += num_str.parse::<usize>().unwrap();
-                }
-                "*" => {
-                    if i == 0 {
-                        results[j] = num_str.parse::<usize>().unwrap();
-                    } else {
-                        results[j] *= num_str.parse::<usize>().unwrap();
-                    }
-                }
-                _ => unreachable!("Only + and * operations are supported"),
-            }
+pub fn solve_part2(input: &str) -> usize {
+    try_solve_part2(input).unwrap()
+}
+
+/// Sums the results of the problems with their numbers read row-wise.
+///
+/// # Errors
+/// Fails if the worksheet can't be parsed or a result overflows.
+pub fn try_solve_part1(input: &str) -> Result<usize, ParseError> {
+    Ok(solve_with_options(
+        input,
+        ReadingMode::RowWise,
+        &WorksheetOptions::default(),
+    )?)
+}
+
+/// Sums the results of the problems with their numbers read column-wise.
+///
+/// # Errors
+/// Fails if the worksheet can't be parsed or a result overflows.
+pub fn try_solve_part2(input: &str) -> Result<usize, ParseError> {
+    Ok(solve_with_options(
+        input,
+        ReadingMode::ColumnWise,
+        &WorksheetOptions::default(),
+    )?)
+}
+
+/// How a worksheet is parsed and evaluated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorksheetOptions {
+    pub order: EvaluationOrder,
+    /// Tabs are expanded to the next multiple of this many columns.
+    pub tab_width: usize,
+}
+
+impl Default for WorksheetOptions {
+    fn default() -> Self {
+        Self {
+            order: EvaluationOrder::LeftToRight,
+            tab_width: DEFAULT_TAB_WIDTH,
         }
     }
+}
 
-    results.iter().sum()
+/// Sums the results of the problems with their numbers read according to `mode`.
+///
+/// # Errors
+/// Fails if the worksheet can't be parsed or a result overflows.
+pub fn solve_with_options(
+    input: &str,
+    mode: ReadingMode,
+    options: &WorksheetOptions,
+) -> Result<usize, Box<dyn Error>> {
+    let problems = parse_problems(input, mode, options)?;
+
+    grand_total(&problems, options.order)
 }
 
-pub fn solve_part2(input: &str) -> usize {
-    let max_line_length = input
-        .lines()
-        .map(|line| line.chars().count())
-        .max()
+pub fn solve_part2_with_transpose(input: &str) -> usize {
+    let problems = parse_problems_with_transpose(input, &WorksheetOptions::default())
+        .map_err(ParseError::from)
         .unwrap();
-    let num_line_count = input.lines().count() - 1;
-    let ops: Vec<(usize, char)> = input
-        .lines()
-        .last()
+
+    grand_total(&problems, EvaluationOrder::LeftToRight)
+        .map_err(ParseError::from)
         .unwrap()
-        .chars()
-        .enumerate()
-        .filter(|(_, c)| !c.is_whitespace())
-        .collect();
-    let mut results = vec![0; ops.len()];
+}
 
-    let num_char_grid: Vec<Vec<char>> = input
-        .lines()
-        .take(num_line_count)
-        .map(|line| {
-            let mut line_chars: Vec<char> = line.chars().collect();
-            line_chars.resize(max_line_length, ' ');
-            line_chars
+/// Sums the results of all problems.
+///
+/// # Errors
+/// Fails with the first problem that can't be evaluated or if the sum overflows.
+pub fn grand_total(problems: &[Problem], order: EvaluationOrder) -> Result<usize, Box<dyn Error>> {
+    problems.iter().try_fold(0usize, |total, problem| {
+        total
+            .checked_add(problem.evaluate(order)?)
+            .ok_or_else(|| "The grand total overflows".into())
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Power,
+    Max,
+    Min,
+    Concatenate,
+}
+
+impl Operator {
+    fn parse(symbol: &str) -> Result<Self, Box<dyn Error>> {
+        match symbol {
+            "+" => Ok(Operator::Add),
+            "-" => Ok(Operator::Subtract),
+            "*" => Ok(Operator::Multiply),
+            "/" => Ok(Operator::Divide),
+            "^" => Ok(Operator::Power),
+            "max" => Ok(Operator::Max),
+            "min" => Ok(Operator::Min),
+            "||" => Ok(Operator::Concatenate),
+            _ => Err(format!("Unknown operator '{}'", symbol).into()),
+        }
+    }
+
+    /// Applies the operator to both operands.
+    ///
+    /// # Returns
+    /// `None` if the result does not fit into a `usize`, is negative or divides by zero.
+    fn apply(self, lhs: usize, rhs: usize) -> Option<usize> {
+        match self {
+            Operator::Add => lhs.checked_add(rhs),
+            Operator::Subtract => lhs.checked_sub(rhs),
+            Operator::Multiply => lhs.checked_mul(rhs),
+            Operator::Divide => lhs.checked_div(rhs),
+            Operator::Power => lhs.checked_pow(u32::try_from(rhs).ok()?),
+            Operator::Max => Some(lhs.max(rhs)),
+            Operator::Min => Some(lhs.min(rhs)),
+            Operator::Concatenate => lhs
+                .checked_mul(10usize.checked_pow(rhs.checked_ilog10().unwrap_or(0) + 1)?)?
+                .checked_add(rhs),
+        }
+    }
+}
+
+impl Display for Operator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let symbol = match self {
+            Operator::Add => "+",
+            Operator::Subtract => "-",
+            Operator::Multiply => "*",
+            Operator::Divide => "/",
+            Operator::Power => "^",
+            Operator::Max => "max",
+            Operator::Min => "min",
+            Operator::Concatenate => "||",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+/// Determines how the operands of a problem are combined for non-associative
+/// or mixed operators, neither order gives an operator precedence over another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EvaluationOrder {
+    /// `a - b * c` is evaluated as `(a - b) * c`.
+    #[default]
+    LeftToRight,
+    /// `a - b * c` is evaluated as `a - (b * c)`.
+    RightToLeft,
+}
+
+impl FromStr for EvaluationOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "left-to-right" => Ok(EvaluationOrder::LeftToRight),
+            "right-to-left" => Ok(EvaluationOrder::RightToLeft),
+            _ => Err(format!(
+                "Unknown evaluation order '{}', expected left-to-right or right-to-left",
+                s
+            )),
+        }
+    }
+}
+
+impl Display for EvaluationOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EvaluationOrder::LeftToRight => write!(f, "left-to-right"),
+            EvaluationOrder::RightToLeft => write!(f, "right-to-left"),
+        }
+    }
+}
+
+/// Determines how the operands of a problem are read from the worksheet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadingMode {
+    /// Every row of a problem block holds one operand, read from top to bottom.
+    RowWise,
+    /// Every column of a problem block holds one operand with its digits written from top to bottom.
+    /// The columns are read from right to left, as cephalopods do.
+    ColumnWise,
+}
+
+/// A single problem of the worksheet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    /// The operator between every two consecutive operands, one less than there are operands.
+    pub operators: Vec<Operator>,
+    /// The operands in reading order.
+    pub operands: Vec<usize>,
+    /// The columns of the worksheet the problem occupies.
+    pub column_span: Range<usize>,
+}
+
+impl Problem {
+    /// Creates a problem from the operators of its block, read in the same direction as the operands.
+    ///
+    /// A single operator is placed between every two operands, otherwise there must be
+    /// exactly one operator between every two operands. A problem needs at least one operand.
+    fn new(
+        operators: Vec<Operator>,
+        operands: Vec<usize>,
+        column_span: Range<usize>,
+    ) -> Result<Self, Box<dyn Error>> {
+        if operands.is_empty() {
+            return Err(format!(
+                "Problem in columns {}..{} has no operands",
+                column_span.start, column_span.end
+            )
+            .into());
+        }
+        let gaps = operands.len().saturating_sub(1);
+        let operators = match operators[..] {
+            [operator] => vec![operator; gaps],
+            _ if operators.len() == gaps => operators,
+            _ => {
+                return Err(format!(
+                    "Problem in columns {}..{}: Expected a single operator or one between every two of its {} operands but found '{}'",
+                    column_span.start,
+                    column_span.end,
+                    operands.len(),
+                    operators.iter().join(" ")
+                )
+                .into());
+            }
+        };
+
+        Ok(Problem {
+            operators,
+            operands,
+            column_span,
         })
-        .collect();
+    }
+
+    /// Returns the operands in reading order with the operators between them, like `3 + 4 * 5`.
+    pub fn expression(&self) -> String {
+        let mut expression = self.operands.iter().take(1).join("");
+        for (operator, operand) in self.operators.iter().zip(self.operands.iter().skip(1)) {
+            expression += &format!(" {} {}", operator, operand);
+        }
+        expression
+    }
 
-    for i in 0..ops.len() {
-        // the block starts at the position of the ith character that is not a whitespace in ops
-        let block_start = ops[i].0;
-        let block_end = if i + 1 < ops.len() {
-            ops[i + 1].0 - 2
-        } else {
-            max_line_length - 1
+    /// Combines the operands in reading order with the operators between them.
+    ///
+    /// # Errors
+    /// Fails if the problem has no operands or an intermediate result overflows,
+    /// becomes negative or divides by zero.
+    pub fn evaluate(&self, order: EvaluationOrder) -> Result<usize, Box<dyn Error>> {
+        let error = || -> Box<dyn Error> {
+            format!(
+                "Problem in columns {}..{} overflows or is undefined: {}",
+                self.column_span.start,
+                self.column_span.end,
+                self.expression()
+            )
+            .into()
         };
-        let operation = ops[i].1;
 
-        for col in (block_start..=block_end).rev() {
-            let mut num_str = String::with_capacity(num_line_count);
+        let result = match order {
+            EvaluationOrder::LeftToRight => {
+                // Pair every operator with its right operand and fold from the first operand
+                let steps = self.operators.iter().zip(self.operands.iter().skip(1));
+                steps.fold(self.operands.first().copied(), |lhs, (operator, &rhs)| {
+                    operator.apply(lhs?, rhs)
+                })
+            }
+            EvaluationOrder::RightToLeft => {
+                // Pair every operator with its left operand and fold from the last operand
+                let steps = self.operators.iter().zip(self.operands.iter()).rev();
+                steps.fold(self.operands.last().copied(), |rhs, (operator, &lhs)| {
+                    operator.apply(lhs, rhs?)
+                })
+            }
+        };
 
-            for row in (0..num_line_count).rev() {
-                let c = num_char_grid[row][col];
-                if !c.is_ascii_digit() && num_str.contains(|c: char| c.is_ascii_digit()) {
-                    break;
-                }
-                num_str.push(c);
+        result.ok_or_else(error)
+    }
+}
+
+/// The operators and the column span of a problem block.
+type Block = (Vec<Operator>, Range<usize>);
+
+/// The worksheet padded to a rectangular grid, split into the number rows and the operator row.
+struct Worksheet {
+    number_rows: Vec<Vec<char>>,
+    operator_row: Vec<char>,
+    /// The line of the operator row, starting at 1.
+    operator_line: usize,
+    width: usize,
+}
+
+impl Worksheet {
+    /// Returns every problem block of the worksheet.
+    ///
+    /// Blocks are the maximal runs of columns that are not blank in every row,
+    /// each block must contain exactly one operator in the operator row.
+    fn blocks(&self) -> Result<Vec<Block>, Box<dyn Error>> {
+        let is_blank_column = |col: usize| {
+            self.number_rows
+                .iter()
+                .chain(std::iter::once(&self.operator_row))
+                .all(|row| row[col].is_whitespace())
+        };
+
+        let mut blocks = Vec::new();
+        let mut col = 0;
+        while col < self.width {
+            if is_blank_column(col) {
+                col += 1;
+                continue;
             }
 
-            let num = num_str
-                .trim()
-                .chars()
-                .rev()
-                .collect::<String>()
-                .parse::<usize>()
-                .unwrap();
-            match operation {
-                '+' => {
-                    results[i] += num;
-                }
-                '*' => {
-                    if col == block_end {
-                        results[i] = num;
-                    } else {
-                        results[i] *= num;
-                    }
-                }
-                _ => unreachable!("Only + and * operations are supported"),
+            let start = col;
+            while col < self.width && !is_blank_column(col) {
+                col += 1;
             }
+            let span = start..col;
+
+            let symbols: String = self.operator_row[span.clone()].iter().collect();
+            let operators = match symbols.split_whitespace().collect::<Vec<&str>>()[..] {
+                [] => Err("Missing operator".into()),
+                ref symbols => symbols
+                    .iter()
+                    .map(|symbol| Operator::parse(symbol))
+                    .collect(),
+            }
+            .map_err(|e| {
+                ParseError::at_line(
+                    self.operator_line,
+                    format!("Problem in columns {}..{}: {}", span.start, span.end, e),
+                )
+            })?;
+
+            blocks.push((operators, span));
         }
+
+        Ok(blocks)
+    }
+
+    fn read_row_wise(&self, span: &Range<usize>) -> Result<Vec<usize>, ParseError> {
+        self.number_rows
+            .iter()
+            .enumerate()
+            .map(|(index, row)| (index, row[span.clone()].iter().collect::<String>()))
+            .filter(|(_, operand)| !operand.trim().is_empty())
+            .map(|(index, operand)| parse_operand(&operand, Some(index + 1)))
+            .collect()
     }
 
-    results.iter().sum()
+    fn read_column_wise(&self, span: &Range<usize>) -> Result<Vec<usize>, ParseError> {
+        span.clone()
+            .rev()
+            .map(|col| {
+                self.number_rows
+                    .iter()
+                    .map(|row| row[col])
+                    .collect::<String>()
+            })
+            .filter(|operand| !operand.trim().is_empty())
+            .map(|operand| parse_operand(&operand, None))
+            .collect()
+    }
 }
 
-pub fn solve_part2_with_transpose(input: &str) -> usize {
-    // Pad the input to a rectangular grid
-    let max_line_length = input
+/// Parses an operand, `line` is only known for operands read row-wise.
+fn parse_operand(operand: &str, line: Option<usize>) -> Result<usize, ParseError> {
+    operand.trim().parse::<usize>().map_err(|_| ParseError {
+        line,
+        message: format!("'{}' is not a valid operand", operand.trim()),
+    })
+}
+
+/// The tab width used to expand tabs when parsing a worksheet, unless the options set another one.
+pub const DEFAULT_TAB_WIDTH: usize = 8;
+
+/// Expands tabs to the next multiple of `tab_width` columns and strips carriage returns.
+///
+/// The parsers normalize their input with the tab width of their [`WorksheetOptions`].
+pub fn normalize_worksheet(input: &str, tab_width: usize) -> String {
+    let tab_width = tab_width.max(1);
+
+    input
+        .lines()
+        .map(|line| {
+            let mut normalized = String::with_capacity(line.len());
+            let mut column = 0;
+            for c in line.chars() {
+                match c {
+                    '\t' => {
+                        let spaces = tab_width - column % tab_width;
+                        normalized.extend(std::iter::repeat_n(' ', spaces));
+                        column += spaces;
+                    }
+                    '\r' => {}
+                    _ => {
+                        normalized.push(c);
+                        column += 1;
+                    }
+                }
+            }
+            normalized
+        })
+        .join("\n")
+}
+
+fn load_worksheet(input: &str, tab_width: usize) -> Result<Worksheet, Box<dyn Error>> {
+    let input = normalize_worksheet(input, tab_width);
+    let lines: Vec<&str> = input
+        .trim_end_matches(|c: char| c.is_whitespace())
         .lines()
+        .collect();
+
+    if lines.len() < 2 {
+        return Err("The worksheet needs at least one number row and an operator row".into());
+    }
+
+    let width = lines
+        .iter()
         .map(|line| line.chars().count())
         .max()
-        .unwrap();
-    let chars: Vec<char> = input
-        .lines()
-        .flat_map(|line| {
+        .unwrap_or(0);
+
+    let mut rows: Vec<Vec<char>> = lines
+        .iter()
+        .map(|line| {
             let mut line_chars: Vec<char> = line.chars().collect();
-            line_chars.resize(max_line_length, ' ');
+            line_chars.resize(width, ' ');
             line_chars
         })
         .collect();
-    let mut transposed_chars = vec![' '; chars.len()];
-    let input_height = input.lines().count();
+    let operator_row = rows.pop().unwrap();
 
-    transpose(&chars, &mut transposed_chars, max_line_length, input_height);
+    Ok(Worksheet {
+        number_rows: rows,
+        operator_row,
+        operator_line: lines.len(),
+        width,
+    })
+}
 
-    let parts: Vec<&[char]> = transposed_chars.chunks(input_height).collect();
+/// Parses every problem of the worksheet, reading the operands according to `mode`.
+pub fn parse_problems(
+    input: &str,
+    mode: ReadingMode,
+    options: &WorksheetOptions,
+) -> Result<Vec<Problem>, Box<dyn Error>> {
+    read_problems(&load_worksheet(input, options.tab_width)?, mode)
+}
 
-    let mut total = 0;
+fn read_problems(worksheet: &Worksheet, mode: ReadingMode) -> Result<Vec<Problem>, Box<dyn Error>> {
+    worksheet
+        .blocks()?
+        .into_iter()
+        .map(|(mut operators, column_span)| {
+            let operands = match mode {
+                ReadingMode::RowWise => worksheet.read_row_wise(&column_span)?,
+                ReadingMode::ColumnWise => {
+                    operators.reverse();
+                    worksheet.read_column_wise(&column_span)?
+                }
+            };
+            Problem::new(operators, operands, column_span)
+        })
+        .collect()
+}
 
-    let mut current_op = parts.iter().nth(0).unwrap().last().unwrap();
+/// Parses every problem of the worksheet column-wise by transposing the number rows first,
+/// so every column of the worksheet can be read as a contiguous slice.
+pub fn parse_problems_with_transpose(
+    input: &str,
+    options: &WorksheetOptions,
+) -> Result<Vec<Problem>, Box<dyn Error>> {
+    let worksheet = load_worksheet(input, options.tab_width)?;
+    let height = worksheet.number_rows.len();
 
-    let mut part_result = match current_op {
-        '+' => 0,
-        '*' => 1,
-        _ => unreachable!("Only + and * operations are supported"),
-    };
+    let chars: Vec<char> = worksheet.number_rows.concat();
+    let mut columns = vec![' '; chars.len()];
+    transpose(&chars, &mut columns, worksheet.width, height);
+    let columns: Vec<&[char]> = columns.chunks(height.max(1)).collect();
 
-    for part in parts {
-        if !part.last().unwrap().is_whitespace() {
-            current_op = part.last().unwrap();
-            part_result = match current_op {
-                '+' => 0,
-                '*' => 1,
-                _ => unreachable!("Only + and * operations are supported"),
-            };
-        }
+    worksheet
+        .blocks()?
+        .into_iter()
+        .map(|(mut operators, column_span)| {
+            operators.reverse();
+            let operands = column_span
+                .clone()
+                .rev()
+                .map(|col| columns[col].iter().join(""))
+                .filter(|operand| !operand.trim().is_empty())
+                .map(|operand| parse_operand(&operand, None))
+                .collect::<Result<Vec<usize>, ParseError>>()?;
+            Problem::new(operators, operands, column_span)
+        })
+        .collect()
+}
 
-        let number_str = part.iter().take(part.len() - 1).join("").trim().to_string();
+/// Describes how every problem of the worksheet is read and evaluated.
+///
+/// Each problem is printed with its column span, the raw worksheet block, the operands
+/// in reading order, the operators and its result, so misaligned columns are easy to spot.
+pub fn explain(
+    input: &str,
+    mode: ReadingMode,
+    options: &WorksheetOptions,
+) -> Result<String, Box<dyn Error>> {
+    let worksheet = load_worksheet(input, options.tab_width)?;
+    let reading_direction = match mode {
+        ReadingMode::RowWise => "top to bottom",
+        ReadingMode::ColumnWise => "right to left",
+    };
+    let mut explanation = String::new();
 
-        if number_str.is_empty() {
-            total += part_result;
-            continue;
+    for (i, problem) in read_problems(&worksheet, mode)?.iter().enumerate() {
+        let span = problem.column_span.clone();
+        explanation += &format!(
+            "Problem {} in columns {}..{}:\n",
+            i + 1,
+            span.start,
+            span.end
+        );
+        for row in worksheet
+            .number_rows
+            .iter()
+            .chain(std::iter::once(&worksheet.operator_row))
+        {
+            explanation += &format!("  |{}|\n", row[span.clone()].iter().collect::<String>());
         }
+        explanation += &format!(
+            "  operands read {}: {}\n",
+            reading_direction,
+            problem.operands.iter().join(", ")
+        );
 
-        let number = number_str.parse::<usize>().unwrap();
-        match current_op {
-            '+' => {
-                part_result += number;
-            }
-            '*' => {
-                part_result *= number;
-            }
-            _ => unreachable!("Only + and * operations are supported"),
+        let expression = problem.expression();
+        match problem.evaluate(options.order) {
+            Ok(result) => explanation += &format!("  {} = {}\n", expression, result),
+            Err(_) => explanation += &format!("  {} = overflows or is undefined\n", expression),
         }
     }
 
-    total + part_result
+    Ok(explanation)
 }
 
 #[cfg(test)]
 mod tests {
-    use std::fs::read_to_string;
-
     use super::*;
+    use crate::fixture_or_skip;
+
+    #[test]
+    fn test_parse_problems() {
+        let input = fixture_or_skip!(6);
+
+        let problems = parse_problems(
+            input.as_str(),
+            ReadingMode::RowWise,
+            &WorksheetOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(problems.len(), 4);
+        assert_eq!(
+            problems[0],
+            Problem {
+                operators: vec![Operator::Multiply; 2],
+                operands: vec![123, 45, 6],
+                column_span: 0..3,
+            }
+        );
+
+        let problems = parse_problems(
+            input.as_str(),
+            ReadingMode::ColumnWise,
+            &WorksheetOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            problems[3],
+            Problem {
+                operators: vec![Operator::Add; 2],
+                operands: vec![4, 431, 623],
+                column_span: 12..15,
+            }
+        );
+        assert_eq!(
+            parse_problems_with_transpose(input.as_str(), &WorksheetOptions::default()).unwrap(),
+            problems
+        );
+    }
+
+    #[test]
+    fn test_unknown_operator() {
+        let result = parse_problems(
+            "1 2\n3 4\n% +",
+            ReadingMode::RowWise,
+            &WorksheetOptions::default(),
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Line 3: Problem in columns 0..1: Unknown operator '%'"
+        );
+    }
+
+    #[test]
+    fn test_normalize_worksheet() {
+        assert_eq!(normalize_worksheet("1\t2\r\n12\t3", 4), "1   2\n12  3");
+        assert_eq!(normalize_worksheet("\t1", 8), "        1");
+    }
+
+    #[test]
+    fn test_ragged_lines_tabs_and_crlf() {
+        let input = fixture_or_skip!(6);
+        let expected = parse_problems(
+            input.as_str(),
+            ReadingMode::ColumnWise,
+            &WorksheetOptions::default(),
+        )
+        .unwrap();
+
+        let ragged = input.lines().map(|line| line.trim_end()).join("\r\n");
+        assert_eq!(
+            parse_problems(
+                &ragged,
+                ReadingMode::ColumnWise,
+                &WorksheetOptions::default()
+            )
+            .unwrap(),
+            expected
+        );
+
+        let short_operator_row = "123 45\n  6  7\n*";
+        let problems = parse_problems(
+            short_operator_row,
+            ReadingMode::RowWise,
+            &WorksheetOptions::default(),
+        );
+        assert_eq!(
+            problems.unwrap_err().to_string(),
+            "Line 3: Problem in columns 4..6: Missing operator"
+        );
+
+        let short_operator_row = "123 45\n  6  7\n*   +\n\n";
+        let problems = parse_problems(
+            short_operator_row,
+            ReadingMode::ColumnWise,
+            &WorksheetOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(problems[1].operands, vec![57, 4]);
+
+        let tabs = "12\t3\n4\t56\n+\t*";
+        let problems =
+            parse_problems(tabs, ReadingMode::RowWise, &WorksheetOptions::default()).unwrap();
+        assert_eq!(problems[0].operands, vec![12, 4]);
+        assert_eq!(problems[1].column_span, 8..10);
+        assert_eq!(problems[1].operands, vec![3, 56]);
+
+        let narrow_tabs = WorksheetOptions {
+            tab_width: 4,
+            ..WorksheetOptions::default()
+        };
+        let problems = parse_problems(tabs, ReadingMode::RowWise, &narrow_tabs).unwrap();
+        assert_eq!(problems[1].column_span, 4..6);
+        assert_eq!(
+            solve_with_options(tabs, ReadingMode::ColumnWise, &narrow_tabs).unwrap(),
+            solve_part2(tabs)
+        );
+    }
+
+    #[test]
+    fn test_malformed_worksheets() {
+        let error = |input: &str| {
+            parse_problems(input, ReadingMode::RowWise, &WorksheetOptions::default())
+                .unwrap_err()
+                .to_string()
+        };
+
+        assert_eq!(
+            error(""),
+            "The worksheet needs at least one number row and an operator row"
+        );
+        assert_eq!(
+            error("123 4\n+ * +"),
+            "Problem in columns 0..3: Expected a single operator or one between every two of its 1 operands but found '+ *'"
+        );
+        assert_eq!(
+            error("12 3\n+"),
+            "Line 2: Problem in columns 3..4: Missing operator"
+        );
+        assert_eq!(error("1x 3\n+  +"), "Line 1: '1x' is not a valid operand");
+        assert_eq!(error("1  \n+ *"), "Problem in columns 2..3 has no operands");
+        assert_eq!(
+            try_solve_part2("1  \n+ *").unwrap_err().to_string(),
+            "Problem in columns 2..3 has no operands"
+        );
+        assert_eq!(
+            try_solve_part1("1 2\n3 4\n\n% +").unwrap_err().line,
+            Some(4)
+        );
+        assert_eq!(try_solve_part1("1 2\n3 x\n+ +").unwrap_err().line, Some(2));
+        assert_eq!(try_solve_part2("1 2\n3 x\n+ +").unwrap_err().line, None);
+    }
+
+    #[test]
+    fn test_extended_operators() {
+        let input = "20  20 20  20  20  7   12\n 5   2  3   3  30  15  3 \n 2   2  2   2  10  3   4 \n-   /  ^   max min ||  + ";
+        let problems =
+            parse_problems(input, ReadingMode::RowWise, &WorksheetOptions::default()).unwrap();
+        let results: Vec<usize> = problems
+            .iter()
+            .map(|p| p.evaluate(EvaluationOrder::LeftToRight).unwrap())
+            .collect();
+        assert_eq!(
+            problems.iter().map(|p| p.operators[0]).collect::<Vec<_>>(),
+            vec![
+                Operator::Subtract,
+                Operator::Divide,
+                Operator::Power,
+                Operator::Max,
+                Operator::Min,
+                Operator::Concatenate,
+                Operator::Add
+            ]
+        );
+        assert_eq!(results, vec![13, 5, 64_000_000, 20, 10, 7153, 19]);
+
+        let results: Vec<usize> = problems
+            .iter()
+            .map(|p| p.evaluate(EvaluationOrder::RightToLeft).unwrap())
+            .collect();
+        assert_eq!(results, vec![17, 20, 20usize.pow(9), 20, 10, 7153, 19]);
+    }
+
+    #[test]
+    fn test_mixed_operators() {
+        let input = "100 700\n  4   2\n  2   3\n- * ^ +";
+        let problems =
+            parse_problems(input, ReadingMode::RowWise, &WorksheetOptions::default()).unwrap();
+        assert_eq!(
+            problems[0].operators,
+            vec![Operator::Subtract, Operator::Multiply]
+        );
+        assert_eq!(problems[0].expression(), "100 - 4 * 2");
+        assert_eq!(problems[1].expression(), "700 ^ 2 + 3");
+
+        let options = WorksheetOptions {
+            order: EvaluationOrder::LeftToRight,
+            ..WorksheetOptions::default()
+        };
+        assert_eq!(
+            solve_with_options(input, ReadingMode::RowWise, &options).unwrap(),
+            (100 - 4) * 2 + (700 * 700 + 3)
+        );
+        let options = WorksheetOptions {
+            order: EvaluationOrder::RightToLeft,
+            ..WorksheetOptions::default()
+        };
+        assert_eq!(
+            solve_with_options(input, ReadingMode::RowWise, &options).unwrap(),
+            (100 - 4 * 2) + 700usize.pow(5)
+        );
+
+        // Read right to left, the operators are reversed along with the operands
+        let problems =
+            parse_problems(input, ReadingMode::ColumnWise, &WorksheetOptions::default()).unwrap();
+        assert_eq!(problems[0].expression(), "42 * 0 - 1");
+
+        assert_eq!(
+            "right-to-left".parse::<EvaluationOrder>(),
+            Ok(EvaluationOrder::RightToLeft)
+        );
+        assert!("outside-in".parse::<EvaluationOrder>().is_err());
+    }
+
+    #[test]
+    fn test_checked_evaluation() {
+        let problem = Problem {
+            operators: vec![Operator::Subtract],
+            operands: vec![3, 5],
+            column_span: 4..6,
+        };
+        assert_eq!(
+            problem
+                .evaluate(EvaluationOrder::LeftToRight)
+                .unwrap_err()
+                .to_string(),
+            "Problem in columns 4..6 overflows or is undefined: 3 - 5"
+        );
+        assert_eq!(
+            problem
+                .evaluate(EvaluationOrder::RightToLeft)
+                .unwrap_err()
+                .to_string(),
+            "Problem in columns 4..6 overflows or is undefined: 3 - 5"
+        );
+
+        let overflowing = Problem {
+            operators: vec![Operator::Power],
+            operands: vec![10, 30],
+            column_span: 0..2,
+        };
+        assert!(overflowing.evaluate(EvaluationOrder::LeftToRight).is_err());
+
+        let division_by_zero = Problem {
+            operators: vec![Operator::Divide],
+            operands: vec![10, 0],
+            column_span: 0..2,
+        };
+        assert!(
+            division_by_zero
+                .evaluate(EvaluationOrder::LeftToRight)
+                .is_err()
+        );
+        assert!(grand_total(&[division_by_zero], EvaluationOrder::LeftToRight).is_err());
+    }
+
+    #[test]
+    fn test_explain() {
+        let input = fixture_or_skip!(6);
+        let explanation = explain(
+            input.as_str(),
+            ReadingMode::ColumnWise,
+            &WorksheetOptions::default(),
+        )
+        .unwrap();
+
+        assert!(explanation.starts_with(
+            "Problem 1 in columns 0..3:\n  |123|\n  | 45|\n  |  6|\n  |*  |\n  operands read right to left: 356, 24, 1\n  356 * 24 * 1 = 8544\n"
+        ));
+        assert!(
+            explanation
+                .ends_with("  operands read right to left: 4, 431, 623\n  4 + 431 + 623 = 1058\n")
+        );
+
+        let explanation = explain(
+            "3\n5\n-",
+            ReadingMode::RowWise,
+            &WorksheetOptions::default(),
+        )
+        .unwrap();
+        assert!(explanation.ends_with("  3 - 5 = overflows or is undefined\n"));
+    }
 
     #[test]
     fn test_solve_part1() {
-        let input = read_to_string("input/day06/example.txt").unwrap();
+        let input = fixture_or_skip!(6);
         let result = solve_part1(input.as_str());
         assert_eq!(result, 4277556);
     }
 
     #[test]
     fn test_solve_part2() {
-        let input = read_to_string("input/day06/example.txt").unwrap();
+        let input = fixture_or_skip!(6);
         let result = solve_part2(input.as_str());
         assert_eq!(result, 3263827);
     }
 
     #[test]
     fn test_solve_part2_with_transpose() {
-        let input = read_to_string("input/day06/example.txt").unwrap();
+        let input = fixture_or_skip!(6);
         let result = solve_part2_with_transpose(input.as_str());
         assert_eq!(result, 3263827);
     }
@@ -1,6 +1,468 @@
-use std::collections::{HashSet, VecDeque};
+use std::{
+    collections::{HashSet, VecDeque},
+    error::Error,
+    fmt::Display,
+    marker::PhantomData,
+};
+
+use num_bigint::BigUint;
+use num_rational::BigRational;
+use num_traits::{One, Zero};
+
+use crate::ParseError;
 
 pub fn solve_part1(input: &str) -> usize {
+    simulate_beams(input, &CellTable::puzzle(), &Presence)
+        .unwrap()
+        .splits
+}
+
+/// Counts the timelines, falling back to wider integers whenever the count overflows.
+pub fn solve_part2(input: &str) -> Timelines {
+    count_timelines_with(input, &CellTable::puzzle())
+}
+
+/// Counts the splits like [`solve_part1`].
+///
+/// Every input is a manifold, glyphs other than `.`, `S` and `^` absorb the beams,
+/// so this never fails. It exists to give every day the same fallible interface.
+pub fn try_solve_part1(input: &str) -> Result<usize, ParseError> {
+    Ok(solve_part1(input))
+}
+
+/// Counts the timelines like [`solve_part2`], which never fails either.
+pub fn try_solve_part2(input: &str) -> Result<Timelines, ParseError> {
+    Ok(solve_part2(input))
+}
+
+/// Counts the timelines like [`solve_part2`] for a manifold with custom cells.
+pub fn count_timelines_with(input: &str, cells: &CellTable) -> Timelines {
+    simulate_timelines(input, cells).timelines
+}
+
+/// Counts the timelines that leave the manifold through its left or right edge
+/// instead of reaching its bottom row.
+pub fn count_exits(input: &str, cells: &CellTable) -> Timelines {
+    simulate_timelines(input, cells).exited
+}
+
+/// Simulates the beams, falling back to wider integers whenever a count overflows.
+fn simulate_timelines(input: &str, cells: &CellTable) -> Simulation<Timelines> {
+    if let Some(simulation) = simulate_beams(input, cells, &Checked::<u64>::new()) {
+        return simulation.map(Timelines::U64);
+    }
+    if let Some(simulation) = simulate_beams(input, cells, &Checked::<u128>::new()) {
+        return simulation.map(Timelines::U128);
+    }
+    simulate_beams(input, cells, &Unbounded)
+        .unwrap()
+        .map(Timelines::Big)
+}
+
+/// Computes the expected number of particles leaving the manifold, through its bottom or sides,
+/// when weighted splitters send each particle to a single random output.
+pub fn expected_exits(input: &str, cells: &CellTable) -> BigRational {
+    simulate_beams(input, cells, &Expected).unwrap().timelines
+}
+
+/// Counts the timelines modulo `modulus`, which is usually a large prime.
+///
+/// # Errors
+/// Fails if the modulus is smaller than 2.
+pub fn count_timelines_modulo(input: &str, modulus: u64) -> Result<u64, Box<dyn Error>> {
+    if modulus < 2 {
+        return Err(format!("The modulus must be at least 2, got {}", modulus).into());
+    }
+
+    let simulation = simulate_beams(input, &CellTable::puzzle(), &Modulo(modulus)).unwrap();
+    Ok(simulation.timelines)
+}
+
+/// A timeline count stored in the smallest integer type it fits into.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Timelines {
+    U64(u64),
+    U128(u128),
+    Big(BigUint),
+}
+
+impl Display for Timelines {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Timelines::U64(count) => write!(f, "{}", count),
+            Timelines::U128(count) => write!(f, "{}", count),
+            Timelines::Big(count) => write!(f, "{}", count),
+        }
+    }
+}
+
+/// The arithmetic used to count the timelines reaching each column.
+trait TimelineArithmetic {
+    type Count: Clone;
+
+    fn zero(&self) -> Self::Count;
+    fn one(&self) -> Self::Count;
+    fn is_zero(&self, count: &Self::Count) -> bool;
+
+    /// Adds two counts, returning `None` if the sum overflows.
+    fn add(&self, lhs: &Self::Count, rhs: &Self::Count) -> Option<Self::Count>;
+
+    /// Returns the share of `count` that takes an output with `weight` out of `total`.
+    ///
+    /// Every output of a weighted splitter is a timeline of its own, so by default
+    /// the whole count is passed on.
+    fn weighted(&self, count: &Self::Count, _weight: u64, _total: u64) -> Option<Self::Count> {
+        Some(count.clone())
+    }
+}
+
+/// Only tracks whether a column holds a beam, which is all that part 1 needs.
+struct Presence;
+
+impl TimelineArithmetic for Presence {
+    type Count = bool;
+
+    fn zero(&self) -> bool {
+        false
+    }
+
+    fn one(&self) -> bool {
+        true
+    }
+
+    fn is_zero(&self, count: &bool) -> bool {
+        !count
+    }
+
+    fn add(&self, lhs: &bool, rhs: &bool) -> Option<bool> {
+        Some(*lhs || *rhs)
+    }
+}
+
+/// Counts with a fixed width integer and reports overflows.
+struct Checked<T>(PhantomData<T>);
+
+impl<T> Checked<T> {
+    fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+macro_rules! impl_checked_arithmetic {
+    ($($t:ty),*) => {
+        $(
+            impl TimelineArithmetic for Checked<$t> {
+                type Count = $t;
+
+                fn zero(&self) -> $t {
+                    0
+                }
+
+                fn one(&self) -> $t {
+                    1
+                }
+
+                fn is_zero(&self, count: &$t) -> bool {
+                    *count == 0
+                }
+
+                fn add(&self, lhs: &$t, rhs: &$t) -> Option<$t> {
+                    lhs.checked_add(*rhs)
+                }
+            }
+        )*
+    };
+}
+
+impl_checked_arithmetic!(u64, u128);
+
+/// Counts with arbitrary precision, never overflows.
+struct Unbounded;
+
+impl TimelineArithmetic for Unbounded {
+    type Count = BigUint;
+
+    fn zero(&self) -> BigUint {
+        BigUint::ZERO
+    }
+
+    fn one(&self) -> BigUint {
+        BigUint::from(1u8)
+    }
+
+    fn is_zero(&self, count: &BigUint) -> bool {
+        *count == BigUint::ZERO
+    }
+
+    fn add(&self, lhs: &BigUint, rhs: &BigUint) -> Option<BigUint> {
+        Some(lhs + rhs)
+    }
+}
+
+/// Counts modulo a fixed modulus.
+///
+/// Columns whose count is a multiple of the modulus are skipped like empty ones,
+/// which is fine as they don't contribute to the result.
+struct Modulo(u64);
+
+impl TimelineArithmetic for Modulo {
+    type Count = u64;
+
+    fn zero(&self) -> u64 {
+        0
+    }
+
+    fn one(&self) -> u64 {
+        1 % self.0
+    }
+
+    fn is_zero(&self, count: &u64) -> bool {
+        *count == 0
+    }
+
+    fn add(&self, lhs: &u64, rhs: &u64) -> Option<u64> {
+        Some(((*lhs as u128 + *rhs as u128) % self.0 as u128) as u64)
+    }
+}
+
+/// Counts the expected number of particles as exact fractions.
+///
+/// Weighted splitters send each particle to one of their outputs at random,
+/// so only the share matching the weight of an output is passed on.
+struct Expected;
+
+impl TimelineArithmetic for Expected {
+    type Count = BigRational;
+
+    fn zero(&self) -> BigRational {
+        BigRational::zero()
+    }
+
+    fn one(&self) -> BigRational {
+        BigRational::one()
+    }
+
+    fn is_zero(&self, count: &BigRational) -> bool {
+        count.is_zero()
+    }
+
+    fn add(&self, lhs: &BigRational, rhs: &BigRational) -> Option<BigRational> {
+        Some(lhs + rhs)
+    }
+
+    fn weighted(&self, count: &BigRational, weight: u64, total: u64) -> Option<BigRational> {
+        Some(count * BigRational::new(weight.into(), total.into()))
+    }
+}
+
+/// What happens to a beam that enters a cell of the manifold.
+///
+/// Offsets are relative to the column of the cell, the outputs continue in the row below.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CellBehaviour {
+    /// The beam continues straight down.
+    Pass,
+    /// The beam is removed.
+    Absorb,
+    /// The beam continues in the column at the given offset, e.g. `-1` for a left deflector.
+    Deflect(isize),
+    /// The beam continues in every column at the given offsets, each output is a new timeline.
+    Split(Vec<isize>),
+    /// The particle takes one of the outputs at random, with a probability proportional
+    /// to its weight. Outputs with a weight of zero are never taken.
+    Weighted(Vec<(isize, u32)>),
+}
+
+/// Maps every glyph of a manifold to the behaviour of its cell.
+///
+/// Glyphs without a mapping absorb the beam, like anything but `.` and `^` in the puzzle.
+/// The source `S` is handled by the simulation and passes the beam through unless it is remapped.
+///
+/// ```
+/// use adventofcode2025::day07::{CellBehaviour, CellTable};
+///
+/// let cells = CellTable::puzzle()
+///     .with(b'<', CellBehaviour::Deflect(-1))
+///     .with(b'?', CellBehaviour::Weighted(vec![(-1, 1), (1, 3)]));
+/// assert_eq!(cells.behaviour(b'<'), &CellBehaviour::Deflect(-1));
+/// assert_eq!(cells.behaviour(b'#'), &CellBehaviour::Absorb);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CellTable {
+    behaviours: [CellBehaviour; 256],
+}
+
+impl CellTable {
+    /// Creates a table where every glyph absorbs the beam.
+    pub fn new() -> Self {
+        Self {
+            behaviours: std::array::from_fn(|_| CellBehaviour::Absorb),
+        }
+    }
+
+    /// The cells of the original puzzle: `.` and `S` pass the beam, `^` splits it to both sides.
+    pub fn puzzle() -> Self {
+        Self::new()
+            .with(b'.', CellBehaviour::Pass)
+            .with(b'S', CellBehaviour::Pass)
+            .with(b'^', CellBehaviour::Split(vec![-1, 1]))
+    }
+
+    pub fn with(mut self, glyph: u8, behaviour: CellBehaviour) -> Self {
+        self.behaviours[glyph as usize] = behaviour;
+        self
+    }
+
+    pub fn behaviour(&self, glyph: u8) -> &CellBehaviour {
+        &self.behaviours[glyph as usize]
+    }
+}
+
+impl Default for CellTable {
+    fn default() -> Self {
+        Self::puzzle()
+    }
+}
+
+/// The outcome of sending the tachyon beams through the manifold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Simulation<C> {
+    /// The number of splitters that were hit by a beam.
+    splits: usize,
+    /// The number of timelines the tachyon particles end up in,
+    /// including the ones that left the manifold sideways.
+    timelines: C,
+    /// The number of timelines that left the manifold through its left or right edge.
+    exited: C,
+}
+
+impl<C> Simulation<C> {
+    fn map<D>(self, f: impl Fn(C) -> D) -> Simulation<D> {
+        Simulation {
+            splits: self.splits,
+            timelines: f(self.timelines),
+            exited: f(self.exited),
+        }
+    }
+}
+
+/// Simulates the beams row by row in a single pass.
+///
+/// Instead of tracking a set of beam positions, the number of timelines that reach each column
+/// is kept in a dense vector. A column with a non-zero count holds a beam, so the split count
+/// of part 1 and the timeline count of part 2 fall out of the same pass. Only the range of
+/// columns between the outermost beams is scanned, which keeps wide manifolds cheap.
+///
+/// Every `S` starts a new beam below it, regardless of the row it is in. What happens to a beam
+/// entering any other cell is looked up in the [`CellTable`]. Outputs that would leave the
+/// manifold through its left or right edge are counted as exited.
+///
+/// # Returns
+/// `None` if a count overflows with the given arithmetic.
+fn simulate_beams<A: TimelineArithmetic>(
+    input: &str,
+    cells: &CellTable,
+    arithmetic: &A,
+) -> Option<Simulation<A::Count>> {
+    simulate_beams_with_visitor(input, cells, arithmetic, |_, _, _| {})
+}
+
+/// Simulates the beams like [`simulate_beams`] and calls `visit` with the row, the column
+/// and the timeline count of every cell a beam enters.
+fn simulate_beams_with_visitor<A: TimelineArithmetic>(
+    input: &str,
+    cells: &CellTable,
+    arithmetic: &A,
+    mut visit: impl FnMut(usize, usize, &A::Count),
+) -> Option<Simulation<A::Count>> {
+    let width = input.lines().map(str::len).max().unwrap_or(0);
+
+    // Both vectors are zero outside of the active columns
+    let mut timelines = vec![arithmetic.zero(); width];
+    let mut next_timelines = vec![arithmetic.zero(); width];
+    let mut active = width..0;
+
+    let mut splits = 0;
+    let mut exited = arithmetic.zero();
+
+    for (y, line) in input.lines().enumerate() {
+        let row = line.as_bytes();
+        for (col, _) in line.match_indices('S') {
+            timelines[col] = arithmetic.add(&timelines[col], &arithmetic.one())?;
+            active = active.start.min(col)..active.end.max(col + 1);
+        }
+
+        let (mut next_start, mut next_end) = (width, 0);
+        let mut add = |col: usize, count: &A::Count| -> Option<()> {
+            next_timelines[col] = arithmetic.add(&next_timelines[col], count)?;
+            next_start = next_start.min(col);
+            next_end = next_end.max(col + 1);
+            Some(())
+        };
+
+        for col in active.clone() {
+            let count = &timelines[col];
+            if arithmetic.is_zero(count) {
+                continue;
+            }
+            visit(y, col, count);
+
+            // Sends the count to the column at `offset`, or out of the manifold if there is none
+            let mut send = |offset: isize, count: &A::Count| -> Option<()> {
+                match col
+                    .checked_add_signed(offset)
+                    .filter(|&target| target < width)
+                {
+                    Some(target) => add(target, count),
+                    None => {
+                        exited = arithmetic.add(&exited, count)?;
+                        Some(())
+                    }
+                }
+            };
+
+            match cells.behaviour(row.get(col).copied().unwrap_or(b'.')) {
+                CellBehaviour::Pass => send(0, count)?,
+                CellBehaviour::Absorb => {}
+                CellBehaviour::Deflect(offset) => send(*offset, count)?,
+                CellBehaviour::Split(offsets) => {
+                    for &offset in offsets {
+                        send(offset, count)?;
+                    }
+                    splits += 1;
+                }
+                CellBehaviour::Weighted(outputs) => {
+                    let total = outputs.iter().map(|&(_, weight)| weight as u64).sum();
+                    for &(offset, weight) in outputs.iter().filter(|&&(_, weight)| weight > 0) {
+                        send(offset, &arithmetic.weighted(count, weight as u64, total)?)?;
+                    }
+                    splits += 1;
+                }
+            }
+        }
+
+        if !active.is_empty() {
+            timelines[active].fill(arithmetic.zero());
+        }
+        std::mem::swap(&mut timelines, &mut next_timelines);
+        active = next_start..next_end;
+    }
+
+    let total = timelines
+        .iter()
+        .try_fold(exited.clone(), |total, count| arithmetic.add(&total, count))?;
+
+    Some(Simulation {
+        splits,
+        timelines: total,
+        exited,
+    })
+}
+
+/// Counts the splits with a set of beam positions, kept as a baseline for benchmarks.
+///
+/// Only supports a single source and splitters on even rows that are not at the edge.
+pub fn solve_part1_with_hash_set(input: &str) -> usize {
     let mut beams: HashSet<usize> = HashSet::with_capacity(1);
     let mut split_count = 0;
     let mut add_beams: VecDeque<usize> = VecDeque::with_capacity(1024);
@@ -37,14 +499,15 @@ pub fn solve_part1(input: &str) -> usize {
             for beam in add_beams.drain(..) {
                 beams.insert(beam);
             }
-
-            // _pretty_print(line, &beams);
         });
 
     split_count
 }
 
-pub fn solve_part2(input: &str) -> usize {
+/// Counts the timelines with a set of beam positions, kept as a baseline for benchmarks.
+///
+/// Only supports a single source and splitters on even rows that are not at the edge.
+pub fn solve_part2_with_hash_set(input: &str) -> usize {
     let capacity = input.find('\n').unwrap();
     let mut beams: HashSet<usize> = HashSet::with_capacity(1);
     let mut path_count = vec![0; capacity];
@@ -76,41 +539,487 @@ pub fn solve_part2(input: &str) -> usize {
                 })
                 .flatten()
                 .collect();
-
-            // _pretty_print(line, &beams);
         });
 
     path_count.iter().sum()
 }
 
-fn _pretty_print(line: &str, beams: &HashSet<usize>) {
-    for (i, ch) in line.chars().enumerate() {
-        if beams.contains(&i) {
-            print!("|");
+/// Renders the manifold with the beam paths for a terminal using ANSI colours.
+///
+/// Every cell a beam passes through is drawn as `|` and coloured by the number of timelines
+/// in it, on a logarithmic scale from blue to red. Splitters that were hit are drawn bold in
+/// the colour of their timeline count, splitters that no beam reached are dimmed.
+pub fn render_ansi(input: &str) -> String {
+    let trace = BeamTrace::new(input);
+    let mut output = String::new();
+
+    for (y, counts) in trace.counts.iter().enumerate() {
+        for (x, &count) in counts.iter().enumerate() {
+            let Some(glyph) = trace.glyph(x, y) else {
+                continue;
+            };
+            let (r, g, b) = heat_colour(trace.heat(count));
+            match (glyph, count > 0.0) {
+                ('^', true) => output.push_str(&format!("\x1b[1;38;2;{r};{g};{b}m^\x1b[0m")),
+                ('^', false) => output.push_str("\x1b[2;90m^\x1b[0m"),
+                ('S', _) => output.push_str("\x1b[1mS\x1b[0m"),
+                ('.', true) => output.push_str(&format!("\x1b[38;2;{r};{g};{b}m|\x1b[0m")),
+                (glyph, _) => output.push(glyph),
+            }
+        }
+        output.push('\n');
+    }
+
+    output.push_str(&trace.summary());
+    output.push('\n');
+    output
+}
+
+/// Renders the manifold with the beam paths as an SVG image.
+///
+/// Uses the same colours as [`render_ansi`], beam cells are filled squares, splitters are
+/// triangles and sources are circles.
+pub fn render_svg(input: &str) -> String {
+    const CELL: usize = 10;
+
+    let trace = BeamTrace::new(input);
+    let width = trace.width * CELL;
+    let height = trace.counts.len() * CELL;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">\n",
+        width,
+        height + 2 * CELL
+    );
+    svg.push_str(&format!(
+        "<rect width=\"{}\" height=\"{}\" fill=\"#101418\"/>\n",
+        width,
+        height + 2 * CELL
+    ));
+
+    for (y, counts) in trace.counts.iter().enumerate() {
+        for (x, &count) in counts.iter().enumerate() {
+            let (left, top) = (x * CELL, y * CELL);
+            let (r, g, b) = heat_colour(trace.heat(count));
+            if count > 0.0 {
+                svg.push_str(&format!(
+                    "<rect x=\"{left}\" y=\"{top}\" width=\"{CELL}\" height=\"{CELL}\" fill=\"rgb({r},{g},{b})\" fill-opacity=\"0.6\"/>\n"
+                ));
+            }
+
+            match trace.cell(x, y) {
+                b'^' => {
+                    let fill = if count > 0.0 { "#f5f5f5" } else { "#4a4f55" };
+                    svg.push_str(&format!(
+                        "<polygon points=\"{},{} {},{} {},{}\" fill=\"{}\"/>\n",
+                        left + CELL / 2,
+                        top + 1,
+                        left + 1,
+                        top + CELL - 1,
+                        left + CELL - 1,
+                        top + CELL - 1,
+                        fill
+                    ));
+                }
+                b'S' => svg.push_str(&format!(
+                    "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"#ffffff\"/>\n",
+                    left + CELL / 2,
+                    top + CELL / 2,
+                    CELL / 2 - 1
+                )),
+                b'.' => {}
+                _ => svg.push_str(&format!(
+                    "<rect x=\"{left}\" y=\"{top}\" width=\"{CELL}\" height=\"{CELL}\" fill=\"#2a2f35\"/>\n"
+                )),
+            }
+        }
+    }
+
+    svg.push_str(&format!(
+        "<text x=\"2\" y=\"{}\" font-family=\"monospace\" font-size=\"{}\" fill=\"#f5f5f5\">{}</text>\n",
+        height + CELL + CELL / 2,
+        CELL,
+        trace.summary()
+    ));
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Counts timelines as floating point numbers, which never overflow in practice.
+///
+/// Only used for rendering, where the magnitude of a count matters but not its exact value.
+struct Approximate;
+
+impl TimelineArithmetic for Approximate {
+    type Count = f64;
+
+    fn zero(&self) -> f64 {
+        0.0
+    }
+
+    fn one(&self) -> f64 {
+        1.0
+    }
+
+    fn is_zero(&self, count: &f64) -> bool {
+        *count == 0.0
+    }
+
+    fn add(&self, lhs: &f64, rhs: &f64) -> Option<f64> {
+        Some(lhs + rhs)
+    }
+}
+
+/// The number of timelines passing through every cell of the manifold.
+struct BeamTrace<'a> {
+    rows: Vec<&'a str>,
+    width: usize,
+    counts: Vec<Vec<f64>>,
+    max_count: f64,
+}
+
+impl<'a> BeamTrace<'a> {
+    fn new(input: &'a str) -> Self {
+        let rows: Vec<&str> = input.lines().collect();
+        let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        let mut counts = vec![vec![0.0; width]; rows.len()];
+
+        simulate_beams_with_visitor(input, &CellTable::puzzle(), &Approximate, |y, x, &count| {
+            counts[y][x] = count
+        });
+        let max_count = counts.iter().flatten().copied().fold(0.0, f64::max);
+
+        Self {
+            rows,
+            width,
+            counts,
+            max_count,
+        }
+    }
+
+    /// Returns the cell at the given position, rows shorter than the manifold are padded with `.`.
+    fn cell(&self, x: usize, y: usize) -> u8 {
+        self.rows[y].as_bytes().get(x).copied().unwrap_or(b'.')
+    }
+
+    /// Returns the character that starts at the given position like [`Self::cell`],
+    /// or `None` within a multi-byte character, which is drawn at its first byte.
+    fn glyph(&self, x: usize, y: usize) -> Option<char> {
+        let row = self.rows[y];
+        if x >= row.len() {
+            return Some('.');
+        }
+        row.get(x..).and_then(|rest| rest.chars().next())
+    }
+
+    /// Maps a timeline count onto `0.0..=1.0` on a logarithmic scale.
+    fn heat(&self, count: f64) -> f64 {
+        if count <= 1.0 || self.max_count <= 1.0 {
+            0.0
         } else {
-            print!("{}", ch);
+            count.ln() / self.max_count.ln()
+        }
+    }
+
+    fn summary(&self) -> String {
+        let (mut hit, mut missed) = (0, 0);
+        for (y, counts) in self.counts.iter().enumerate() {
+            for (x, &count) in counts.iter().enumerate() {
+                if self.cell(x, y) == b'^' {
+                    if count > 0.0 {
+                        hit += 1;
+                    } else {
+                        missed += 1;
+                    }
+                }
+            }
         }
+        format!(
+            "Splitters hit: {}, never reached: {}, most timelines in a cell: {}",
+            hit, missed, self.max_count
+        )
     }
-    print!("\n");
+}
+
+/// Interpolates from blue over yellow to red for a heat in `0.0..=1.0`.
+fn heat_colour(heat: f64) -> (u8, u8, u8) {
+    const STOPS: [(f64, f64, f64); 3] = [
+        (40.0, 110.0, 255.0),
+        (250.0, 220.0, 40.0),
+        (230.0, 30.0, 30.0),
+    ];
+
+    let scaled = heat.clamp(0.0, 1.0) * 2.0;
+    let index = (scaled as usize).min(1);
+    let t = scaled - index as f64;
+    let (from, to) = (STOPS[index], STOPS[index + 1]);
+    let mix = |a: f64, b: f64| (a + (b - a) * t).round() as u8;
+    (mix(from.0, to.0), mix(from.1, to.1), mix(from.2, to.2))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    use std::fs::read_to_string;
+    use crate::fixture_or_skip;
 
     #[test]
     fn test_solve_part1() {
-        let input = read_to_string("input/day07/example.txt").unwrap();
+        let input = fixture_or_skip!(7);
         let result = solve_part1(input.as_str());
         assert_eq!(result, 21);
     }
 
     #[test]
     fn test_solve_part2() {
-        let input = read_to_string("input/day07/example.txt").unwrap();
+        let input = fixture_or_skip!(7);
         let result = solve_part2(input.as_str());
-        assert_eq!(result, 40);
+        assert_eq!(result, Timelines::U64(40));
+    }
+
+    #[test]
+    fn test_solve_with_hash_set() {
+        let input = fixture_or_skip!(7);
+        assert_eq!(solve_part1_with_hash_set(input.as_str()), 21);
+        assert_eq!(solve_part2_with_hash_set(input.as_str()), 40);
+    }
+
+    #[test]
+    fn test_adjacent_splitters() {
+        let input = "..S..\n..^..\n.^^^.\n.....";
+        assert_eq!(
+            simulate_beams(input, &CellTable::puzzle(), &Checked::<u64>::new()).unwrap(),
+            Simulation {
+                splits: 3,
+                timelines: 4,
+                exited: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_absorbed_beams() {
+        let input = "..S..\n..^..\n.#.#.\n.....";
+        assert_eq!(
+            simulate_beams(input, &CellTable::puzzle(), &Checked::<u64>::new()).unwrap(),
+            Simulation {
+                splits: 1,
+                timelines: 0,
+                exited: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_splitters_at_the_edges() {
+        let input = ".S.\n...\n.^.\n^.^\n...";
+        assert_eq!(
+            simulate_beams(input, &CellTable::puzzle(), &Checked::<u64>::new()).unwrap(),
+            Simulation {
+                splits: 3,
+                timelines: 4,
+                exited: 2
+            }
+        );
+
+        let input = "S\n^\n.";
+        assert_eq!(
+            simulate_beams(input, &CellTable::puzzle(), &Checked::<u64>::new()).unwrap(),
+            Simulation {
+                splits: 1,
+                timelines: 2,
+                exited: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_count_exits() {
+        let input = ".S.\n...\n.^.\n^.^\n...";
+        assert_eq!(count_exits(input, &CellTable::puzzle()), Timelines::U64(2));
+        assert_eq!(
+            count_exits("..S..\n..^..\n.....", &CellTable::puzzle()),
+            Timelines::U64(0)
+        );
+    }
+
+    #[test]
+    fn test_splitters_on_every_row() {
+        let input = "...S...\n...^...\n..^.^..\n.......\n.^.....";
+        assert_eq!(
+            simulate_beams(input, &CellTable::puzzle(), &Checked::<u64>::new()).unwrap(),
+            Simulation {
+                splits: 4,
+                timelines: 5,
+                exited: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_multiple_sources() {
+        let input = ".S...S.\n.^.....\n...S.^.\n.......";
+        assert_eq!(
+            simulate_beams(input, &CellTable::puzzle(), &Checked::<u64>::new()).unwrap(),
+            Simulation {
+                splits: 2,
+                timelines: 5,
+                exited: 0
+            }
+        );
+    }
+
+    /// Builds a manifold where every beam hits a splitter on each of the `depth` rows,
+    /// so it ends up in `2^depth` timelines.
+    fn doubling_manifold(depth: usize) -> String {
+        let width = 2 * depth + 3;
+        let center = width / 2;
+        let mut rows = vec![format!("{}S{}", ".".repeat(center), ".".repeat(center))];
+        for row in 0..depth {
+            rows.push(
+                (0..width)
+                    .map(|col| {
+                        if (col + center + row).is_multiple_of(2) {
+                            '^'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect(),
+            );
+        }
+        rows.join("\n")
+    }
+
+    #[test]
+    fn test_timelines_fall_back_to_wider_integers() {
+        let input = doubling_manifold(10);
+        assert_eq!(solve_part2(&input), Timelines::U64(1 << 10));
+        assert_eq!(solve_part1(&input), (1..=10).sum());
+
+        let input = doubling_manifold(64);
+        assert!(simulate_beams(&input, &CellTable::puzzle(), &Checked::<u64>::new()).is_none());
+        assert_eq!(solve_part2(&input), Timelines::U128(1 << 64));
+
+        let input = doubling_manifold(130);
+        assert_eq!(
+            solve_part2(&input),
+            Timelines::Big(BigUint::from(1u8) << 130)
+        );
+        assert_eq!(
+            solve_part2(&input).to_string(),
+            "1361129467683753853853498429727072845824"
+        );
+    }
+
+    fn variant_cells() -> CellTable {
+        CellTable::puzzle()
+            .with(b'<', CellBehaviour::Deflect(-1))
+            .with(b'>', CellBehaviour::Deflect(1))
+            .with(b'W', CellBehaviour::Split(vec![-1, 0, 1]))
+            .with(b'?', CellBehaviour::Weighted(vec![(-1, 1), (1, 3)]))
+    }
+
+    #[test]
+    fn test_deflectors() {
+        let input = ".S.\n.>.\n..<\n...";
+        assert_eq!(
+            count_timelines_with(input, &variant_cells()),
+            Timelines::U64(1)
+        );
+
+        let input = "S\n<\n.";
+        assert_eq!(
+            simulate_beams(input, &variant_cells(), &Checked::<u64>::new()).unwrap(),
+            Simulation {
+                splits: 0,
+                timelines: 1,
+                exited: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_splitters_with_more_outputs() {
+        let input = "..S..\n..W..\n.#...\n.....";
+        assert_eq!(
+            simulate_beams(input, &variant_cells(), &Checked::<u64>::new()).unwrap(),
+            Simulation {
+                splits: 1,
+                timelines: 2,
+                exited: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_expected_exits() {
+        let input = "..S..\n..?..\n.#...\n.....";
+        assert_eq!(
+            expected_exits(input, &variant_cells()),
+            BigRational::new(3.into(), 4.into())
+        );
+        assert_eq!(
+            count_timelines_with(input, &variant_cells()),
+            Timelines::U64(1)
+        );
+
+        let input = "...S...\n...?...\n..?.?..\n.......";
+        assert_eq!(expected_exits(input, &variant_cells()), BigRational::one());
+
+        let input = fixture_or_skip!(7);
+        assert_eq!(
+            expected_exits(&input, &CellTable::puzzle()),
+            BigRational::from_integer(40.into())
+        );
+    }
+
+    #[test]
+    fn test_render_ansi() {
+        let input = "..S..\n..^..\n.....\n^....";
+        let rendered = render_ansi(input);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[1].contains("\x1b[1;38;2;"));
+        assert!(lines[3].contains("\x1b[2;90m^"));
+        assert_eq!(lines[2].matches('|').count(), 2);
+        assert_eq!(
+            lines[4],
+            "Splitters hit: 1, never reached: 1, most timelines in a cell: 1"
+        );
+
+        let rendered = render_ansi("S.\né·\n");
+        assert_eq!(rendered.lines().nth(1), Some("é·"));
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let input = "..S..\n..^..\n.....";
+        let svg = render_svg(input);
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<polygon").count(), 1);
+        assert_eq!(svg.matches("<circle").count(), 1);
+    }
+
+    #[test]
+    fn test_heat_colour() {
+        assert_eq!(heat_colour(0.0), (40, 110, 255));
+        assert_eq!(heat_colour(0.5), (250, 220, 40));
+        assert_eq!(heat_colour(1.0), (230, 30, 30));
+    }
+
+    #[test]
+    fn test_count_timelines_modulo() {
+        let input = doubling_manifold(130);
+        let modulus: u64 = 1_000_000_007;
+        let expected: BigUint = (BigUint::from(1u8) << 130) % BigUint::from(modulus);
+        assert_eq!(
+            count_timelines_modulo(&input, modulus).unwrap().to_string(),
+            expected.to_string()
+        );
+
+        let input = "..S..\n..^..\n.....\n.^.^.\n.....";
+        assert_eq!(count_timelines_modulo(input, 2).unwrap(), 0);
+        assert_eq!(count_timelines_modulo(input, 3).unwrap(), 1);
+        assert!(count_timelines_modulo(input, 1).is_err());
     }
 }
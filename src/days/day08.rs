@@ -1,209 +1,747 @@
 use std::{
-    cmp::Ordering,
-    collections::{BinaryHeap, HashSet},
+    cmp::Reverse,
+    collections::{BTreeMap, BinaryHeap, HashMap},
     error::Error,
+    fmt::{Display, Write},
     hash::Hash,
+    str::FromStr,
 };
 
+use crate::{KdTree, Metric, Neighbour, ParseError, Point, UnionFind};
+
 pub fn solve_part1(input: &str) -> usize {
-    solve_part1_with_size(input, 1000)
+    try_solve_part1(input).unwrap()
 }
 
 pub fn solve_part2(input: &str) -> usize {
-    let mut electrical_system = load_electrical_system(input);
+    try_solve_part2(input).unwrap()
+}
+
+/// Solves part 1 with the puzzle's options, see [`solve_part1_with_options`].
+///
+/// # Errors
+/// Fails if a line is not a junction box.
+pub fn try_solve_part1(input: &str) -> Result<usize, ParseError> {
+    Ok(solve_part1_with_options(input, &CircuitOptions::default())?)
+}
+
+/// Solves part 2 with the Euclidean metric, see [`solve_part2_with_metric`].
+///
+/// # Errors
+/// Fails if a line is not a junction box.
+pub fn try_solve_part2(input: &str) -> Result<usize, ParseError> {
+    Ok(solve_part2_with_metric(input, Metric::Euclidean)?)
+}
+
+/// The parameters of part 1 and the metric that measures the cable between two junction boxes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitOptions {
+    /// The number of shortest connections to make.
+    pub connections: usize,
+    /// The number of largest circuits whose sizes are multiplied.
+    pub largest: usize,
+    pub metric: Metric,
+}
 
-    for i in 0..electrical_system.junction_boxes.len() {
-        for j in (i + 1)..electrical_system.junction_boxes.len() {
-            let from = JunctionBoxId(i);
-            let to = JunctionBoxId(j);
-            electrical_system.create_connection(from, to);
+impl Default for CircuitOptions {
+    fn default() -> Self {
+        Self {
+            connections: 1000,
+            largest: 3,
+            metric: Metric::Euclidean,
         }
     }
+}
+
+/// Makes the shortest connections and multiplies the sizes of the largest circuits.
+///
+/// # Errors
+/// Fails if the junction boxes can't be parsed.
+pub fn solve_part1_with_options(
+    input: &str,
+    options: &CircuitOptions,
+) -> Result<usize, Box<dyn Error>> {
+    let mut electrical_system = load_electrical_system(input, options.metric)?;
+
+    for connection in electrical_system.shortest_connections(options.connections) {
+        electrical_system.add_connection_to_circuit(&connection);
+    }
+
+    Ok(largest_circuits_product(
+        &electrical_system,
+        options.largest,
+    ))
+}
+
+/// Multiplies the first coordinates of the two junction boxes whose connection
+/// joins the last two circuits.
+///
+/// # Errors
+/// Fails if the junction boxes can't be parsed or if there are less than two of them.
+pub fn solve_part2_with_metric(input: &str, metric: Metric) -> Result<usize, Box<dyn Error>> {
+    let electrical_system = load_electrical_system(input, metric)?;
+
+    // The connection that joins the last two circuits is the longest one of a minimum spanning tree
+    let last_connection = electrical_system
+        .minimum_spanning_tree()
+        .into_iter()
+        .max()
+        .ok_or("Failed to connect all junction boxes into a single circuit")?;
+
+    electrical_system.first_coordinates_product(&last_connection)
+}
+
+/// Solves part 1 by pushing every pair into a heap, kept as a baseline for benchmarks.
+pub fn solve_part1_with_heap(input: &str) -> usize {
+    let mut electrical_system = load_electrical_system(input, Metric::Euclidean).unwrap();
+    electrical_system.create_all_connections();
+    electrical_system.process_k_connections(1000);
+
+    largest_circuits_product(&electrical_system, 3)
+}
+
+/// Solves part 2 by pushing every pair into a heap, kept as a baseline for benchmarks.
+pub fn solve_part2_with_heap(input: &str) -> usize {
+    let mut electrical_system = load_electrical_system(input, Metric::Euclidean).unwrap();
+    electrical_system.create_all_connections();
 
     let last_connection = electrical_system
         .process_until_single_circuit()
         .expect("Failed to connect all junction boxes into a single circuit");
 
-    electrical_system.junction_boxes[last_connection.from.0].x as usize
-        * electrical_system.junction_boxes[last_connection.to.0].x as usize
+    electrical_system
+        .first_coordinates_product(&last_connection)
+        .unwrap()
 }
 
-fn solve_part1_with_size(input: &str, k: usize) -> usize {
-    let mut electrical_system = load_electrical_system(input);
+/// Makes the shortest connections like part 1 and reports the resulting circuits.
+///
+/// # Errors
+/// Fails if the junction boxes can't be parsed.
+pub fn analyze_network(input: &str, options: &CircuitOptions) -> Result<Network, Box<dyn Error>> {
+    let mut electrical_system = load_electrical_system(input, options.metric)?;
+    let connections = electrical_system.shortest_connections(options.connections);
+    for connection in &connections {
+        electrical_system.add_connection_to_circuit(connection);
+    }
 
-    for i in 0..electrical_system.junction_boxes.len() {
-        for j in (i + 1)..electrical_system.junction_boxes.len() {
-            let from = JunctionBoxId(i);
-            let to = JunctionBoxId(j);
-            electrical_system.create_connection(from, to);
+    Ok(electrical_system.network(&connections))
+}
+
+/// Answers all queries in a single pass over the connections in processing order.
+///
+/// # Errors
+/// Fails if the junction boxes can't be parsed or a query refers to a junction box that doesn't exist.
+pub fn answer_queries(
+    input: &str,
+    metric: Metric,
+    queries: &[Query],
+) -> Result<Vec<Option<Answer>>, Box<dyn Error>> {
+    let electrical_system = load_electrical_system(input, metric)?;
+    let len = electrical_system.junction_boxes.len();
+    for query in queries {
+        if let Query::Connected(a, b) = query
+            && let Some(missing) = [a, b].into_iter().find(|&&index| index >= len)
+        {
+            return Err(format!(
+                "Query '{}': Junction box {} doesn't exist, there are only {}",
+                query, missing, len
+            )
+            .into());
         }
     }
 
-    electrical_system.process_k_connections(k);
+    Ok(electrical_system.answer_queries(queries))
+}
 
-    let mut circuit_sizes: Vec<usize> = electrical_system
-        .circuits
-        .iter()
-        .map(|c| c.junction_boxes.len())
-        .collect();
+/// A question about the circuits while the connections are made one after the other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Query {
+    /// After how many connections do the two junction boxes first share a circuit?
+    Connected(usize, usize),
+    /// After how many connections does the largest circuit first have more junction boxes than this?
+    LargestExceeds(usize),
+    /// Which connection merges the last two circuits into one?
+    LastMerge,
+}
 
-    circuit_sizes.sort_unstable_by(|a, b| b.cmp(a));
-    circuit_sizes.iter().take(3).product()
+impl FromStr for Query {
+    type Err = String;
+
+    /// Parses `connected A B`, `exceeds S` or `last-merge`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        let number = |word: &str| {
+            word.parse::<usize>()
+                .map_err(|_| format!("Query '{}': '{}' is not a valid number", s, word))
+        };
+
+        match words.as_slice() {
+            ["connected", a, b] => Ok(Query::Connected(number(a)?, number(b)?)),
+            ["exceeds", size] => Ok(Query::LargestExceeds(number(size)?)),
+            ["last-merge"] => Ok(Query::LastMerge),
+            _ => Err(format!(
+                "Unknown query '{}', expected 'connected A B', 'exceeds S' or 'last-merge'",
+                s
+            )),
+        }
+    }
 }
 
-fn load_electrical_system(input: &str) -> ElectricalSystem {
-    let num_boxes = input.lines().count();
-    let mut electrical_system = ElectricalSystem::new(num_boxes);
+impl Display for Query {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Query::Connected(a, b) => write!(f, "connected {} {}", a, b),
+            Query::LargestExceeds(size) => write!(f, "exceeds {}", size),
+            Query::LastMerge => write!(f, "last-merge"),
+        }
+    }
+}
 
-    input.lines().for_each(|line| {
-        let coords: Vec<f64> = line
-            .split(',')
-            .map(|num| num.trim().parse::<f64>().unwrap())
-            .collect();
-        electrical_system.add_junction_box((coords[0], coords[1], coords[2]), num_boxes);
-    });
+/// The moment a query became true.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Answer {
+    /// The number of connections made so far, including the ones within a circuit.
+    pub connections: usize,
+    /// The junction boxes of the connection that made the query true,
+    /// `None` if it was true before any connection was made.
+    pub connection: Option<(usize, usize)>,
+}
 
-    electrical_system
+impl Display for Answer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.connection {
+            Some((from, to)) => write!(
+                f,
+                "after {} connection{}, by connecting {} and {}",
+                self.connections,
+                if self.connections == 1 { "" } else { "s" },
+                from,
+                to
+            ),
+            None => write!(f, "before any connection"),
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct JunctionBoxId(usize);
+/// The circuits formed after making a number of connections.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Network {
+    /// The position of every junction box, indexed like the input lines.
+    pub positions: Vec<Vec<i64>>,
+    /// Every circuit, largest first, ties ordered by their lowest junction box.
+    pub circuits: Vec<Circuit>,
+}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct CircuitId(usize);
+/// A set of junction boxes that are connected to each other.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Circuit {
+    /// The indices of the member junction boxes in ascending order.
+    pub junction_boxes: Vec<usize>,
+    /// The connections made within the circuit in the order they were made,
+    /// including the ones between boxes that were already connected.
+    pub connections: Vec<Cable>,
+    /// The summed length of all connections of the circuit, measured with the chosen metric.
+    pub cable_length: f64,
+}
 
-#[derive(Debug)]
-struct JunctionBox {
-    x: f64,
-    y: f64,
-    z: f64,
-    circuit: CircuitId,
+/// A connection that was made between two junction boxes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cable {
+    pub from: usize,
+    pub to: usize,
+    pub length: f64,
 }
 
-impl JunctionBox {
-    fn distance_to_squared(&self, other: &JunctionBox) -> f64 {
-        (self.x - other.x).powi(2) + (self.y - other.y).powi(2) + (self.z - other.z).powi(2)
+impl Network {
+    /// Maps each circuit size to the number of circuits of that size.
+    pub fn size_histogram(&self) -> BTreeMap<usize, usize> {
+        let mut histogram = BTreeMap::new();
+        for circuit in &self.circuits {
+            *histogram.entry(circuit.junction_boxes.len()).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    /// Exports the network as an undirected Graphviz graph with a cluster for every circuit
+    /// that has more than one junction box.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("graph network {\n    node [shape=box];\n");
+        for (number, circuit) in self.circuits.iter().enumerate() {
+            let indent = if circuit.junction_boxes.len() > 1 {
+                writeln!(
+                    dot,
+                    "    subgraph cluster_{} {{\n        label=\"circuit {}: {} boxes, cable {:.2}\";",
+                    number,
+                    number,
+                    circuit.junction_boxes.len(),
+                    circuit.cable_length
+                )
+                .unwrap();
+                "        "
+            } else {
+                "    "
+            };
+
+            for &index in &circuit.junction_boxes {
+                writeln!(
+                    dot,
+                    "{}{} [label=\"{}: {}\"];",
+                    indent,
+                    index,
+                    index,
+                    join_coordinates(&self.positions[index])
+                )
+                .unwrap();
+            }
+            for cable in &circuit.connections {
+                writeln!(
+                    dot,
+                    "{}{} -- {} [label=\"{:.2}\"];",
+                    indent, cable.from, cable.to, cable.length
+                )
+                .unwrap();
+            }
+
+            if circuit.junction_boxes.len() > 1 {
+                dot.push_str("    }\n");
+            }
+        }
+        dot.push_str("}\n");
+        dot
     }
 
-    fn distance_to(&self, other: &JunctionBox) -> f64 {
-        self.distance_to_squared(other).sqrt()
+    /// Exports the positions, the circuits and the size histogram as JSON.
+    pub fn to_json(&self) -> String {
+        let join = |items: Vec<String>| items.join(",");
+
+        let positions = join(
+            self.positions
+                .iter()
+                .map(|position| format!("[{}]", join_coordinates(position)))
+                .collect(),
+        );
+        let circuits = join(
+            self.circuits
+                .iter()
+                .map(|circuit| {
+                    let connections = join(
+                        circuit
+                            .connections
+                            .iter()
+                            .map(|cable| {
+                                format!(
+                                    "{{\"from\":{},\"to\":{},\"length\":{}}}",
+                                    cable.from, cable.to, cable.length
+                                )
+                            })
+                            .collect(),
+                    );
+                    format!(
+                        "{{\"junction_boxes\":[{}],\"connections\":[{}],\"cable_length\":{}}}",
+                        join(
+                            circuit
+                                .junction_boxes
+                                .iter()
+                                .map(usize::to_string)
+                                .collect()
+                        ),
+                        connections,
+                        circuit.cable_length
+                    )
+                })
+                .collect(),
+        );
+        let histogram = join(
+            self.size_histogram()
+                .iter()
+                .map(|(size, count)| format!("\"{}\":{}", size, count))
+                .collect(),
+        );
+
+        format!(
+            "{{\"positions\":[{}],\"circuits\":[{}],\"size_histogram\":{{{}}}}}\n",
+            positions, circuits, histogram
+        )
     }
 }
 
-#[derive(Debug, Clone)]
-struct Connection {
-    from: JunctionBoxId,
-    to: JunctionBoxId,
-    distance: f64,
+fn join_coordinates(position: &[i64]) -> String {
+    position
+        .iter()
+        .map(i64::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
 }
 
-impl PartialEq for Connection {
-    fn eq(&self, other: &Self) -> bool {
-        self.distance == other.distance
-            && (self.from == other.from && self.to == other.to
-                || self.from == other.to && self.to == other.from)
-    }
+fn largest_circuits_product(electrical_system: &ElectricalSystem, largest: usize) -> usize {
+    let mut circuit_sizes = electrical_system.circuits.component_sizes();
+
+    circuit_sizes.sort_unstable_by(|a, b| b.cmp(a));
+    circuit_sizes.iter().take(largest).product()
 }
 
-impl Eq for Connection {}
+/// The largest absolute value of a coordinate.
+pub(crate) const MAX_COORDINATE: i64 = 1 << 24;
+/// The most coordinates of a junction box, so that squared distances always fit into an `i64`.
+const MAX_DIMENSIONS: usize = 1 << 12;
+
+/// Parses one junction box per line, every line needs the same number of coordinates.
+fn load_electrical_system(input: &str, metric: Metric) -> Result<ElectricalSystem, Box<dyn Error>> {
+    let mut junction_boxes: Vec<JunctionBox> = Vec::new();
 
-impl PartialOrd for Connection {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        // Reverse order for min-heap behavior
-        other.distance.partial_cmp(&self.distance)
+    for (index, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let position = line
+            .split(',')
+            .map(|coordinate| {
+                let value = coordinate.trim().parse::<i64>().map_err(|_| {
+                    ParseError::at_line(
+                        index + 1,
+                        format!("'{}' is not a valid coordinate", coordinate.trim()),
+                    )
+                })?;
+                if value.abs() > MAX_COORDINATE {
+                    return Err(ParseError::at_line(
+                        index + 1,
+                        format!(
+                            "Coordinate {} is outside of -{}..={}",
+                            value, MAX_COORDINATE, MAX_COORDINATE
+                        ),
+                    ));
+                }
+                Ok(value)
+            })
+            .collect::<Result<Vec<i64>, ParseError>>()?;
+
+        if position.len() > MAX_DIMENSIONS {
+            return Err(ParseError::at_line(
+                index + 1,
+                format!(
+                    "Junction boxes have at most {} coordinates but found {}",
+                    MAX_DIMENSIONS,
+                    position.len()
+                ),
+            )
+            .into());
+        }
+        if let Some(first) = junction_boxes.first()
+            && first.position.len() != position.len()
+        {
+            return Err(ParseError::at_line(
+                index + 1,
+                format!(
+                    "Expected {} coordinates like the first junction box but found {}",
+                    first.position.len(),
+                    position.len()
+                ),
+            )
+            .into());
+        }
+        junction_boxes.push(JunctionBox { position });
     }
+
+    Ok(ElectricalSystem::new(junction_boxes, metric))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct JunctionBoxId(usize);
+
+/// A junction box with as many coordinates as the input lines have.
+#[derive(Debug)]
+struct JunctionBox {
+    position: Vec<i64>,
 }
 
-impl Ord for Connection {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.partial_cmp(other).unwrap_or(Ordering::Equal)
+impl Point for JunctionBox {
+    fn dimensions(&self) -> usize {
+        self.position.len()
+    }
+
+    fn coordinate(&self, axis: usize) -> i64 {
+        self.position[axis]
     }
 }
 
-#[derive(Debug)]
-struct Circuit {
-    junction_boxes: HashSet<JunctionBoxId>,
+/// A possible connection between two junction boxes, where `from` is always the lower index.
+///
+/// Connections are ordered by their distance first, as returned by [`Metric::distance`],
+/// ties are broken by `from` and then by `to`. This is a total order, so the connection
+/// processed next never depends on the order of the input pairs or on heap internals,
+/// and the field order must not change.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Connection {
+    distance: i64,
+    from: JunctionBoxId,
+    to: JunctionBoxId,
 }
 
+/// The junction boxes, the connections that can still be made between them
+/// and the circuits formed by the connections made so far.
 #[derive(Debug)]
 struct ElectricalSystem {
     junction_boxes: Vec<JunctionBox>,
-    connections: BinaryHeap<Connection>,
-    circuits: Vec<Circuit>,
+    metric: Metric,
+    /// Min-heap of every possible connection, only filled for the heap baselines.
+    connections: BinaryHeap<Reverse<Connection>>,
+    circuits: UnionFind,
 }
 
 impl ElectricalSystem {
-    fn new(size: usize) -> Self {
+    fn new(junction_boxes: Vec<JunctionBox>, metric: Metric) -> Self {
+        let circuits = UnionFind::new(junction_boxes.len());
         Self {
-            junction_boxes: Vec::with_capacity(size),
-            connections: BinaryHeap::with_capacity(size * size),
-            circuits: Vec::with_capacity(size),
+            junction_boxes,
+            metric,
+            connections: BinaryHeap::new(),
+            circuits,
         }
     }
 
-    fn add_junction_box(&mut self, (x, y, z): (f64, f64, f64), circuit_capacity: usize) {
-        let circuit_id = CircuitId(self.circuits.len());
-        self.junction_boxes.push(JunctionBox {
-            x,
-            y,
-            z,
-            circuit: circuit_id,
-        });
-        self.circuits.push(Circuit {
-            junction_boxes: HashSet::with_capacity(circuit_capacity),
-        });
-        self.circuits[circuit_id.0]
-            .junction_boxes
-            .insert(JunctionBoxId(self.junction_boxes.len() - 1));
+    fn connection(&self, a: JunctionBoxId, b: JunctionBoxId) -> Connection {
+        let distance = self
+            .metric
+            .distance(&self.junction_boxes[a.0], &self.junction_boxes[b.0]);
+        Connection {
+            distance,
+            from: a.min(b),
+            to: a.max(b),
+        }
     }
 
-    fn create_connection(&mut self, from: JunctionBoxId, to: JunctionBoxId) {
-        let distance = self.junction_boxes[from.0].distance_to(&self.junction_boxes[to.0]);
-        let connection = Connection { from, to, distance };
-        self.connections.push(connection);
+    /// Pushes every pair of junction boxes into the heap, which takes `O(n²)` memory.
+    fn create_all_connections(&mut self) {
+        let connections: Vec<Reverse<Connection>> = (0..self.junction_boxes.len())
+            .flat_map(|i| {
+                ((i + 1)..self.junction_boxes.len())
+                    .map(move |j| (JunctionBoxId(i), JunctionBoxId(j)))
+            })
+            .map(|(from, to)| Reverse(self.connection(from, to)))
+            .collect();
+        self.connections.extend(connections);
     }
 
-    fn get_circuit_id_for_junction_box(&self, junction_box_id: JunctionBoxId) -> CircuitId {
-        self.junction_boxes[junction_box_id.0].circuit
+    /// Returns the `k` shortest connections, shortest first.
+    fn shortest_connections(&self, k: usize) -> Vec<Connection> {
+        self.connections_in_order().take(k).collect()
     }
 
-    fn merge_circuits(&mut self, circuit_a: CircuitId, circuit_b: CircuitId) {
-        if circuit_a == circuit_b {
-            return;
+    /// Returns every connection in the processing order defined by [`Connection`],
+    /// generating them lazily from a k-d tree over the junction boxes.
+    fn connections_in_order(&self) -> ConnectionsInOrder<'_> {
+        ConnectionsInOrder::new(self)
+    }
+
+    /// Returns the connections of a minimum spanning tree over all junction boxes.
+    ///
+    /// Uses Prim's algorithm on the implicit complete graph, which takes `O(n²)` time
+    /// but only `O(n)` memory as no connection is stored before it joins the tree. The lazy
+    /// connection stream is not used here, as a single distant junction box makes it fetch
+    /// almost every neighbour of every box. Candidates are compared with the total order of
+    /// [`Connection`], so the tree is unique and its longest connection is the one that
+    /// joins the last two circuits.
+    fn minimum_spanning_tree(&self) -> Vec<Connection> {
+        let len = self.junction_boxes.len();
+        let mut in_tree = vec![false; len];
+        // The shortest known connection from the tree to every box outside of it
+        let mut nearest: Vec<Option<Connection>> = vec![None; len];
+        let mut tree = Vec::with_capacity(len.saturating_sub(1));
+
+        let mut next = (len > 0).then_some(0);
+        while let Some(current) = next {
+            in_tree[current] = true;
+            if let Some(connection) = nearest[current].take() {
+                tree.push(connection);
+            }
+
+            next = None;
+            for other in (0..len).filter(|&other| !in_tree[other]) {
+                let connection = self.connection(JunctionBoxId(current), JunctionBoxId(other));
+                if nearest[other]
+                    .as_ref()
+                    .is_none_or(|known| connection < *known)
+                {
+                    nearest[other] = Some(connection);
+                }
+                if next.is_none_or(|best: usize| nearest[other] < nearest[best]) {
+                    next = Some(other);
+                }
+            }
         }
 
-        let (smaller_id, larger_id) = if self.circuits[circuit_a.0].junction_boxes.len()
-            < self.circuits[circuit_b.0].junction_boxes.len()
-        {
-            (circuit_a, circuit_b)
-        } else {
-            (circuit_b, circuit_a)
-        };
+        tree
+    }
+
+    /// # Errors
+    /// Fails if the product is negative.
+    fn first_coordinates_product(&self, connection: &Connection) -> Result<usize, Box<dyn Error>> {
+        // Fits into an i64 as the coordinates are at most MAX_COORDINATE
+        let product = self.junction_boxes[connection.from.0].position[0]
+            * self.junction_boxes[connection.to.0].position[0];
+        usize::try_from(product).map_err(|_| {
+            format!(
+                "The product {} of the first coordinates of the last connection is negative",
+                product
+            )
+            .into()
+        })
+    }
+
+    /// Answers the queries in a single pass over the connections in processing order,
+    /// `None` for queries that never become true.
+    ///
+    /// Pending `Connected` queries are kept in a list per circuit. When two circuits merge,
+    /// the queries of the shorter list are checked and moved into the longer one, so every
+    /// query is moved `O(log q)` times. `LargestExceeds` queries are sorted by their size and
+    /// answered whenever the largest circuit grows. The pass stops as soon as all queries are
+    /// answered or everything is a single circuit.
+    fn answer_queries(&self, queries: &[Query]) -> Vec<Option<Answer>> {
+        let len = self.junction_boxes.len();
+        let before_any = Some(Answer {
+            connections: 0,
+            connection: None,
+        });
+        let mut answers: Vec<Option<Answer>> = vec![None; queries.len()];
+        let mut pending: Vec<Vec<usize>> = vec![Vec::new(); len];
+        let mut thresholds: Vec<(usize, usize)> = Vec::new();
+        let mut last_merges: Vec<usize> = Vec::new();
+
+        for (index, &query) in queries.iter().enumerate() {
+            match query {
+                Query::Connected(a, b) if a == b => answers[index] = before_any,
+                Query::Connected(a, b) => {
+                    pending[a].push(index);
+                    pending[b].push(index);
+                }
+                Query::LargestExceeds(size) => thresholds.push((size, index)),
+                Query::LastMerge => last_merges.push(index),
+            }
+        }
+        thresholds.sort_unstable();
+
+        let mut largest = len.min(1);
+        let mut next_threshold = thresholds.partition_point(|&(size, _)| size < largest);
+        for &(_, index) in &thresholds[..next_threshold] {
+            answers[index] = before_any;
+        }
 
-        let smaller_junction_boxes = self.circuits[smaller_id.0].junction_boxes.clone();
+        let mut unanswered = answers.iter().filter(|answer| answer.is_none()).count();
+        let mut circuits = UnionFind::new(len);
 
-        let larger_circuit = &mut self.circuits[larger_id.0];
-        for &junction_box_id in &smaller_junction_boxes {
-            larger_circuit.junction_boxes.insert(junction_box_id);
-            self.junction_boxes[junction_box_id.0].circuit = larger_id;
+        for (count, connection) in self.connections_in_order().enumerate() {
+            if unanswered == 0 || circuits.component_count() <= 1 {
+                break;
+            }
+
+            let (from, to) = (connection.from.0, connection.to.0);
+            let (root_from, root_to) = (circuits.find(from), circuits.find(to));
+            if !circuits.union(from, to) {
+                continue;
+            }
+
+            let answer = Some(Answer {
+                connections: count + 1,
+                connection: Some((from, to)),
+            });
+            let mut answer_query = |index: usize, answers: &mut Vec<Option<Answer>>| {
+                if answers[index].is_none() {
+                    answers[index] = answer;
+                    unanswered -= 1;
+                }
+            };
+
+            let root = circuits.find(from);
+            let absorbed = if root == root_from {
+                root_to
+            } else {
+                root_from
+            };
+            let mut moved = std::mem::take(&mut pending[absorbed]);
+            if moved.len() > pending[root].len() {
+                std::mem::swap(&mut moved, &mut pending[root]);
+            }
+            for index in moved {
+                if answers[index].is_some() {
+                    continue;
+                }
+                let Query::Connected(a, b) = queries[index] else {
+                    unreachable!("Only connected queries are pending");
+                };
+                if circuits.connected(a, b) {
+                    answer_query(index, &mut answers);
+                } else {
+                    pending[root].push(index);
+                }
+            }
+
+            largest = largest.max(circuits.component_size(root));
+            while next_threshold < thresholds.len() && thresholds[next_threshold].0 < largest {
+                answer_query(thresholds[next_threshold].1, &mut answers);
+                next_threshold += 1;
+            }
+
+            if circuits.component_count() == 1 {
+                for &index in &last_merges {
+                    answer_query(index, &mut answers);
+                }
+            }
         }
 
-        self.circuits[smaller_id.0].junction_boxes.clear();
+        answers
     }
 
-    fn add_connection_to_circuit(&mut self, connection: Connection) {
-        let from_circuit_id = self.get_circuit_id_for_junction_box(connection.from);
-        let to_circuit_id = self.get_circuit_id_for_junction_box(connection.to);
+    /// Groups the junction boxes by circuit and assigns each of the given connections,
+    /// which must be the ones made so far, to the circuit it is part of.
+    fn network(&mut self, connections: &[Connection]) -> Network {
+        let mut circuit_of_root: HashMap<usize, usize> = HashMap::new();
+        let mut circuits: Vec<Circuit> = Vec::new();
+        for index in 0..self.junction_boxes.len() {
+            let root = self.circuits.find(index);
+            let circuit = *circuit_of_root.entry(root).or_insert_with(|| {
+                circuits.push(Circuit {
+                    junction_boxes: Vec::new(),
+                    connections: Vec::new(),
+                    cable_length: 0.0,
+                });
+                circuits.len() - 1
+            });
+            circuits[circuit].junction_boxes.push(index);
+        }
+
+        for connection in connections {
+            let circuit = &mut circuits[circuit_of_root[&self.circuits.find(connection.from.0)]];
+            let length = self.metric.length(connection.distance);
+            circuit.connections.push(Cable {
+                from: connection.from.0,
+                to: connection.to.0,
+                length,
+            });
+            circuit.cable_length += length;
+        }
 
-        if from_circuit_id != to_circuit_id {
-            self.merge_circuits(from_circuit_id, to_circuit_id);
+        // Circuits were created in the order of their lowest junction box, which breaks ties
+        circuits.sort_by_key(|circuit| Reverse(circuit.junction_boxes.len()));
+        Network {
+            positions: self
+                .junction_boxes
+                .iter()
+                .map(|junction_box| junction_box.position.clone())
+                .collect(),
+            circuits,
         }
     }
 
+    fn add_connection_to_circuit(&mut self, connection: &Connection) {
+        self.circuits.union(connection.from.0, connection.to.0);
+    }
+
     fn process_k_connections(&mut self, k: usize) {
         for _ in 0..k {
-            if let Some(connection) = self.connections.pop() {
-                self.add_connection_to_circuit(connection);
+            if let Some(Reverse(connection)) = self.connections.pop() {
+                self.add_connection_to_circuit(&connection);
             } else {
                 break;
             }
@@ -211,21 +749,95 @@ impl ElectricalSystem {
     }
 
     fn process_until_single_circuit(&mut self) -> Result<Connection, Box<dyn Error>> {
-        loop {
-            if let Some(connection) = self.connections.pop() {
-                self.add_connection_to_circuit(connection.clone());
+        while let Some(Reverse(connection)) = self.connections.pop() {
+            self.add_connection_to_circuit(&connection);
+            if self.circuits.component_count() == 1 {
+                return Ok(connection);
+            }
+        }
+        Err("Not enough connections to form a single circuit".into())
+    }
+}
+
+/// Iterates over all connections in ascending order without storing all pairs.
+///
+/// Every junction box has a stream of its neighbours sorted by distance, which is read from the
+/// k-d tree in batches that double in size whenever the stream runs dry. A heap holds the next
+/// connection of every stream and merges them. Each connection is part of the streams of both
+/// of its ends, as both copies compare equal they are popped one after the other and the second
+/// one is skipped.
+struct ConnectionsInOrder<'a> {
+    electrical_system: &'a ElectricalSystem,
+    tree: KdTree<Vec<i64>>,
+    /// The neighbours fetched so far for every junction box, closest first.
+    neighbours: Vec<Vec<Neighbour>>,
+    /// The position of the next unread neighbour of every junction box.
+    next: Vec<usize>,
+    heads: BinaryHeap<Reverse<(Connection, JunctionBoxId)>>,
+    last: Option<Connection>,
+}
 
-                let non_empty_circuits: Vec<&Circuit> = self
-                    .circuits
-                    .iter()
-                    .filter(|c| !c.junction_boxes.is_empty())
-                    .collect();
+impl<'a> ConnectionsInOrder<'a> {
+    fn new(electrical_system: &'a ElectricalSystem) -> Self {
+        let positions = electrical_system
+            .junction_boxes
+            .iter()
+            .map(|junction_box| junction_box.position.clone())
+            .collect();
+        let len = electrical_system.junction_boxes.len();
 
-                if non_empty_circuits.len() == 1 {
-                    return Ok(connection);
+        let mut connections = Self {
+            electrical_system,
+            tree: KdTree::new(positions),
+            neighbours: vec![Vec::new(); len],
+            next: vec![0; len],
+            heads: BinaryHeap::with_capacity(len),
+            last: None,
+        };
+        for id in (0..len).map(JunctionBoxId) {
+            connections.advance(id);
+        }
+        connections
+    }
+
+    /// Pushes the next connection of the junction box into the heap, if it has any left.
+    fn advance(&mut self, id: JunctionBoxId) {
+        loop {
+            let neighbours = &mut self.neighbours[id.0];
+            if self.next[id.0] == neighbours.len() {
+                if neighbours.len() == self.tree.len() {
+                    return;
                 }
-            } else {
-                return Err("Not enough connections to form a single circuit".into());
+                let k = (2 * neighbours.len()).max(2).min(self.tree.len());
+                *neighbours =
+                    self.tree
+                        .nearest_by(self.tree.point(id.0), k, self.electrical_system.metric);
+            }
+
+            let neighbour = self.neighbours[id.0][self.next[id.0]];
+            self.next[id.0] += 1;
+            // Skip the box itself, other boxes at the same position are at distance zero
+            if neighbour.index != id.0 {
+                let connection = self
+                    .electrical_system
+                    .connection(id, JunctionBoxId(neighbour.index));
+                self.heads.push(Reverse((connection, id)));
+                return;
+            }
+        }
+    }
+}
+
+impl Iterator for ConnectionsInOrder<'_> {
+    type Item = Connection;
+
+    fn next(&mut self) -> Option<Connection> {
+        loop {
+            let Reverse((connection, id)) = self.heads.pop()?;
+            self.advance(id);
+            if self.last.as_ref() != Some(&connection) {
+                self.last = Some(connection.clone());
+                return Some(connection);
             }
         }
     }
@@ -234,20 +846,332 @@ impl ElectricalSystem {
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    use std::fs::read_to_string;
+    use crate::{GeneratorOptions, fixture_or_skip, generate_input};
 
     #[test]
     fn test_solve_part1() {
-        let input = read_to_string("input/day08/example.txt").unwrap();
-        let result = solve_part1_with_size(&input, 10);
+        let input = fixture_or_skip!(8);
+        let options = CircuitOptions {
+            connections: 10,
+            ..CircuitOptions::default()
+        };
+        let result = solve_part1_with_options(&input, &options).unwrap();
         assert_eq!(result, 40);
     }
 
     #[test]
     fn test_solve_part2() {
-        let input = read_to_string("input/day08/example.txt").unwrap();
+        let input = fixture_or_skip!(8);
         let result = solve_part2(&input);
         assert_eq!(result, 25272);
     }
+
+    /// Generates `count` junction boxes at pseudo-random positions.
+    fn generate_junction_boxes(count: usize) -> String {
+        let options = GeneratorOptions {
+            seed: 0,
+            size: count,
+            width: None,
+        };
+        generate_input(8, &options).unwrap()
+    }
+
+    #[test]
+    fn test_matches_heap_baseline() {
+        let input = generate_junction_boxes(1500);
+        assert_eq!(solve_part1(&input), solve_part1_with_heap(&input));
+        assert_eq!(solve_part2(&input), solve_part2_with_heap(&input));
+    }
+
+    #[test]
+    fn test_distant_outlier() {
+        let input = format!(
+            "{}\n10000000,10000000,10000000",
+            generate_junction_boxes(300)
+        );
+        let electrical_system = load_electrical_system(&input, Metric::Euclidean).unwrap();
+        let tree = electrical_system.minimum_spanning_tree();
+        assert_eq!(tree.len(), 300);
+        assert_eq!(tree.iter().max().map(|c| c.to.0), Some(300));
+        assert_eq!(solve_part2(&input), solve_part2_with_heap(&input));
+    }
+
+    #[test]
+    fn test_ties_are_broken_by_junction_box_ids() {
+        let input = "3,0,0\n0,0,0\n1,0,0\n2,0,0";
+        let electrical_system = load_electrical_system(input, Metric::Euclidean).unwrap();
+        let pairs: Vec<(usize, usize)> = electrical_system
+            .shortest_connections(3)
+            .iter()
+            .map(|connection| (connection.from.0, connection.to.0))
+            .collect();
+        assert_eq!(pairs, vec![(0, 3), (1, 2), (2, 3)]);
+
+        let tree = electrical_system.minimum_spanning_tree();
+        assert_eq!(tree.iter().max().map(|c| (c.from.0, c.to.0)), Some((2, 3)));
+        assert_eq!(solve_part2(input), solve_part2_with_heap(input));
+    }
+
+    #[test]
+    fn test_connections_in_order_match_sorted_pairs() {
+        // Few distinct coordinates produce many equally long connections and shared positions
+        let input = generate_junction_boxes(150)
+            .lines()
+            .map(|line| {
+                line.split(',')
+                    .map(|coordinate| (coordinate.parse::<i64>().unwrap() % 5).to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        for metric in [Metric::Euclidean, Metric::Manhattan, Metric::Chebyshev] {
+            let mut electrical_system = load_electrical_system(&input, metric).unwrap();
+            let lazy: Vec<Connection> = electrical_system.connections_in_order().collect();
+
+            electrical_system.create_all_connections();
+            let sorted: Vec<Connection> = electrical_system
+                .connections
+                .into_sorted_vec()
+                .into_iter()
+                .rev()
+                .map(|Reverse(connection)| connection)
+                .collect();
+            assert_eq!(lazy.len(), 150 * 149 / 2);
+            assert_eq!(lazy, sorted);
+        }
+    }
+
+    #[test]
+    fn test_analyze_network() {
+        let input = fixture_or_skip!(8);
+        let options = CircuitOptions {
+            connections: 10,
+            ..CircuitOptions::default()
+        };
+        let network = analyze_network(&input, &options).unwrap();
+
+        let sizes: Vec<usize> = network
+            .circuits
+            .iter()
+            .map(|circuit| circuit.junction_boxes.len())
+            .collect();
+        assert_eq!(sizes[..3], [5, 4, 2]);
+        assert_eq!(
+            network.size_histogram(),
+            BTreeMap::from([(1, 7), (2, 2), (4, 1), (5, 1)])
+        );
+        assert_eq!(
+            network
+                .circuits
+                .iter()
+                .map(|circuit| circuit.connections.len())
+                .sum::<usize>(),
+            10
+        );
+
+        let largest = &network.circuits[0];
+        let cable_length: f64 = largest.connections.iter().map(|cable| cable.length).sum();
+        assert_eq!(largest.cable_length, cable_length);
+        assert!(largest.connections.iter().all(|cable| {
+            largest.junction_boxes.contains(&cable.from)
+                && largest.junction_boxes.contains(&cable.to)
+        }));
+    }
+
+    #[test]
+    fn test_export_network() {
+        let options = CircuitOptions {
+            connections: 1,
+            ..CircuitOptions::default()
+        };
+        let network = analyze_network("0,0,0\n3,4,0\n100,0,0", &options).unwrap();
+        assert_eq!(
+            network.to_json(),
+            "{\"positions\":[[0,0,0],[3,4,0],[100,0,0]],\"circuits\":[\
+             {\"junction_boxes\":[0,1],\"connections\":[{\"from\":0,\"to\":1,\"length\":5}],\"cable_length\":5},\
+             {\"junction_boxes\":[2],\"connections\":[],\"cable_length\":0}],\
+             \"size_histogram\":{\"1\":1,\"2\":1}}\n"
+        );
+
+        let dot = network.to_dot();
+        assert!(dot.starts_with("graph network {"));
+        assert!(dot.contains("subgraph cluster_0 {"));
+        assert!(dot.contains("0 -- 1 [label=\"5.00\"];"));
+        assert!(dot.contains("2 [label=\"2: 100,0,0\"];"));
+        assert!(!dot.contains("cluster_1"));
+    }
+
+    #[test]
+    fn test_dimensions_and_metrics() {
+        let input = "0,0\n2,2\n3,0\n10,10\n13,10";
+        let options = |metric| CircuitOptions {
+            connections: 3,
+            largest: 2,
+            metric,
+        };
+        // Euclidean makes 1-2 (5), 0-1 (8) and 0-2 (9), which wins the tie with 3-4 on its ids
+        assert_eq!(
+            solve_part1_with_options(input, &options(Metric::Euclidean)).unwrap(),
+            3
+        );
+        // Manhattan makes 0-2, 1-2 and 3-4, which are all 3 long
+        assert_eq!(
+            solve_part1_with_options(input, &options(Metric::Manhattan)).unwrap(),
+            6
+        );
+        assert_eq!(
+            solve_part2_with_metric(input, Metric::Euclidean).unwrap(),
+            20
+        );
+        assert_eq!(
+            solve_part2_with_metric(input, Metric::Chebyshev).unwrap(),
+            20
+        );
+
+        let input = "1,2,3,4\n1,2,3,5\n9,9,9,9";
+        assert_eq!(
+            solve_part2_with_metric(input, Metric::Manhattan).unwrap(),
+            9
+        );
+    }
+
+    #[test]
+    fn test_largest_coordinates_do_not_overflow() {
+        let corners = |dimensions: usize| {
+            [-MAX_COORDINATE, MAX_COORDINATE, 0]
+                .map(|coordinate| vec![coordinate.to_string(); dimensions].join(","))
+                .join("\n")
+        };
+        for metric in [Metric::Euclidean, Metric::Manhattan, Metric::Chebyshev] {
+            for input in [corners(3), corners(MAX_DIMENSIONS)] {
+                let options = CircuitOptions {
+                    connections: 3,
+                    metric,
+                    ..CircuitOptions::default()
+                };
+                assert_eq!(solve_part1_with_options(&input, &options).unwrap(), 3);
+                assert_eq!(solve_part2_with_metric(&input, metric).unwrap(), 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_junction_boxes() {
+        assert_eq!(
+            load_electrical_system("1,2,3\n4,5", Metric::Euclidean)
+                .unwrap_err()
+                .to_string(),
+            "Line 2: Expected 3 coordinates like the first junction box but found 2"
+        );
+        assert_eq!(
+            load_electrical_system("1,2,3\n4,x,6", Metric::Euclidean)
+                .unwrap_err()
+                .to_string(),
+            "Line 2: 'x' is not a valid coordinate"
+        );
+        assert!(solve_part2_with_metric("1,2,3", Metric::Euclidean).is_err());
+
+        assert_eq!(
+            try_solve_part1("16777216,0\n-16777217,0")
+                .unwrap_err()
+                .to_string(),
+            "Line 2: Coordinate -16777217 is outside of -16777216..=16777216"
+        );
+        assert_eq!(try_solve_part2("1,2,3\n\n4,5").unwrap_err().line, Some(3));
+        assert!(try_solve_part1(&vec!["0"; MAX_DIMENSIONS + 1].join(",")).is_err());
+        assert_eq!(
+            try_solve_part2("-4,0\n3,0").unwrap_err().to_string(),
+            "The product -12 of the first coordinates of the last connection is negative"
+        );
+    }
+
+    #[test]
+    fn test_answer_queries() {
+        let input = fixture_or_skip!(8);
+        let queries: Vec<Query> = [
+            "connected 0 19",
+            "connected 0 7",
+            "connected 4 4",
+            "exceeds 0",
+            "exceeds 1",
+            "exceeds 4",
+            "exceeds 20",
+            "last-merge",
+        ]
+        .iter()
+        .map(|query| query.parse().unwrap())
+        .collect();
+        let answers = answer_queries(&input, Metric::Euclidean, &queries).unwrap();
+
+        // Compare with replaying the connections one by one
+        let electrical_system = load_electrical_system(&input, Metric::Euclidean).unwrap();
+        let connections: Vec<Connection> = electrical_system.connections_in_order().collect();
+        let replay = |query: &Query| {
+            let mut circuits = UnionFind::new(20);
+            for count in 0..=connections.len() {
+                let holds = match *query {
+                    Query::Connected(a, b) => circuits.connected(a, b),
+                    Query::LargestExceeds(size) => {
+                        (0..20).any(|i| circuits.component_size(i) > size)
+                    }
+                    Query::LastMerge => circuits.component_count() == 1,
+                };
+                if holds {
+                    return Some(count);
+                }
+                if let Some(connection) = connections.get(count) {
+                    circuits.union(connection.from.0, connection.to.0);
+                }
+            }
+            None
+        };
+
+        for (query, answer) in queries.iter().zip(&answers) {
+            assert_eq!(
+                answer.map(|answer| answer.connections),
+                replay(query),
+                "{}",
+                query
+            );
+        }
+        assert_eq!(answers[0].unwrap().connection, Some((0, 19)));
+        assert_eq!(answers[2].unwrap().connection, None);
+        assert_eq!(answers[6], None);
+        assert_eq!(
+            answers[7].unwrap().connection.map(|(from, to)| {
+                electrical_system.junction_boxes[from].position[0]
+                    * electrical_system.junction_boxes[to].position[0]
+            }),
+            Some(25272)
+        );
+    }
+
+    #[test]
+    fn test_invalid_queries() {
+        assert!("connected 1".parse::<Query>().is_err());
+        assert!("exceeds x".parse::<Query>().is_err());
+        assert_eq!("last-merge".parse::<Query>(), Ok(Query::LastMerge));
+        assert_eq!(
+            answer_queries("0,0,0\n1,1,1", Metric::Euclidean, &[Query::Connected(0, 2)])
+                .unwrap_err()
+                .to_string(),
+            "Query 'connected 0 2': Junction box 2 doesn't exist, there are only 2"
+        );
+        assert_eq!(
+            answer_queries("0,0,0", Metric::Euclidean, &[Query::LastMerge]).unwrap(),
+            vec![None]
+        );
+    }
+
+    #[test]
+    fn test_shortest_connections() {
+        let input = fixture_or_skip!(8);
+        let electrical_system = load_electrical_system(&input, Metric::Euclidean).unwrap();
+        let shortest = electrical_system.shortest_connections(5);
+        assert_eq!(shortest.len(), 5);
+        assert!(shortest.is_sorted());
+        assert!(electrical_system.shortest_connections(0).is_empty());
+        assert_eq!(electrical_system.minimum_spanning_tree().len(), 19);
+    }
 }
@@ -0,0 +1,610 @@
+use std::{
+    cell::Cell,
+    error::Error,
+    fmt::Display,
+    fs,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    sync::Once,
+};
+
+use crate::{
+    GeneratorOptions, day01, day02, day03, day04, day05, day06, day07, day08, generate_input,
+};
+
+/// One way of solving a part of a day, with its answer formatted as a string.
+#[derive(Debug, Clone, Copy)]
+pub struct Implementation {
+    pub name: &'static str,
+    pub solve: fn(&str) -> String,
+}
+
+/// The answer of an implementation, or the message it panicked with.
+pub type Outcome = Result<String, String>;
+
+/// How an input is split into units when shrinking a counterexample.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shrink {
+    Lines,
+    /// Comma separated items on a single line.
+    Items,
+    /// The first line is kept, the remaining lines are removed in pairs.
+    /// This keeps every row of a manifold on its parity.
+    RowPairs,
+}
+
+/// A part of a day with every implementation that solves it.
+#[derive(Debug, Clone)]
+pub struct Part {
+    pub day: u32,
+    pub part: u32,
+    pub implementations: Vec<Implementation>,
+    shrink: Shrink,
+}
+
+/// An input on which the implementations of a part disagree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Counterexample {
+    pub day: u32,
+    pub part: u32,
+    pub input: String,
+    pub outcomes: Vec<(&'static str, Outcome)>,
+}
+
+/// The generated inputs a differential run checks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DifferentialOptions {
+    /// Only check this day, all days if `None`.
+    pub day: Option<u32>,
+    /// The seed of the first generated input, every run uses the next one.
+    pub seed: u64,
+    pub runs: u64,
+    /// The size the inputs are generated with, see [`GeneratorOptions`].
+    pub size: usize,
+}
+
+macro_rules! implementation {
+    ($name:literal, $solve:path) => {
+        Implementation {
+            name: $name,
+            solve: |input| $solve(input).to_string(),
+        }
+    };
+}
+
+/// Returns every part that has more than one implementation.
+pub fn registered_parts() -> Vec<Part> {
+    vec![
+        Part::new(
+            1,
+            2,
+            Shrink::Lines,
+            vec![
+                implementation!("solve_part2", day01::solve_part2),
+                implementation!("solve_part2_with_clicks", day01::solve_part2_with_clicks),
+            ],
+        ),
+        Part::new(
+            2,
+            1,
+            Shrink::Items,
+            vec![
+                implementation!("solve_part1", day02::solve_part1),
+                implementation!(
+                    "solve_part1_with_id_counts",
+                    day02::solve_part1_with_id_counts
+                ),
+            ],
+        ),
+        Part::new(
+            2,
+            2,
+            Shrink::Items,
+            vec![
+                implementation!("solve_part2", day02::solve_part2),
+                implementation!(
+                    "solve_part2_with_id_counts",
+                    day02::solve_part2_with_id_counts
+                ),
+            ],
+        ),
+        Part::new(
+            3,
+            1,
+            Shrink::Lines,
+            vec![
+                implementation!("solve_part1", day03::solve_part1),
+                implementation!(
+                    "solve_part1_with_brute_force",
+                    day03::solve_part1_with_brute_force
+                ),
+            ],
+        ),
+        Part::new(
+            4,
+            1,
+            Shrink::Lines,
+            vec![
+                implementation!("solve_part1", day04::solve_part1),
+                implementation!("solve_part1_with_bitset", day04::solve_part1_with_bitset),
+            ],
+        ),
+        Part::new(
+            4,
+            2,
+            Shrink::Lines,
+            vec![
+                implementation!("solve_part2", day04::solve_part2),
+                implementation!("solve_part2_with_bitset", day04::solve_part2_with_bitset),
+            ],
+        ),
+        Part::new(
+            5,
+            1,
+            Shrink::Lines,
+            vec![
+                implementation!("solve_part1", day05::solve_part1),
+                implementation!(
+                    "solve_part1_with_linear_scan",
+                    day05::solve_part1_with_linear_scan
+                ),
+            ],
+        ),
+        Part::new(
+            5,
+            2,
+            Shrink::Lines,
+            vec![
+                implementation!("solve_part2", day05::solve_part2),
+                implementation!(
+                    "solve_part2_with_sorted_ranges",
+                    day05::solve_part2_with_sorted_ranges
+                ),
+            ],
+        ),
+        Part::new(
+            6,
+            2,
+            Shrink::Lines,
+            vec![
+                implementation!("solve_part2", day06::solve_part2),
+                implementation!(
+                    "solve_part2_with_transpose",
+                    day06::solve_part2_with_transpose
+                ),
+            ],
+        ),
+        Part::new(
+            7,
+            1,
+            Shrink::RowPairs,
+            vec![
+                implementation!("solve_part1", day07::solve_part1),
+                implementation!(
+                    "solve_part1_with_hash_set",
+                    day07::solve_part1_with_hash_set
+                ),
+            ],
+        ),
+        Part::new(
+            7,
+            2,
+            Shrink::RowPairs,
+            vec![
+                implementation!("solve_part2", day07::solve_part2),
+                implementation!(
+                    "solve_part2_with_hash_set",
+                    day07::solve_part2_with_hash_set
+                ),
+            ],
+        ),
+        Part::new(
+            8,
+            1,
+            Shrink::Lines,
+            vec![
+                implementation!("solve_part1", day08::solve_part1),
+                implementation!("solve_part1_with_heap", day08::solve_part1_with_heap),
+            ],
+        ),
+        Part::new(
+            8,
+            2,
+            Shrink::Lines,
+            vec![
+                implementation!("solve_part2", day08::solve_part2),
+                implementation!("solve_part2_with_heap", day08::solve_part2_with_heap),
+            ],
+        ),
+    ]
+}
+
+impl Part {
+    fn new(day: u32, part: u32, shrink: Shrink, implementations: Vec<Implementation>) -> Self {
+        Self {
+            day,
+            part,
+            implementations,
+            shrink,
+        }
+    }
+
+    /// Runs every implementation on the input, catching panics.
+    pub fn outcomes(&self, input: &str) -> Vec<(&'static str, Outcome)> {
+        silence_panics(true);
+        let outcomes = self
+            .implementations
+            .iter()
+            .map(|implementation| {
+                let outcome =
+                    panic::catch_unwind(AssertUnwindSafe(|| (implementation.solve)(input)))
+                        .map_err(|payload| {
+                            payload
+                                .downcast_ref::<&str>()
+                                .map(|message| message.to_string())
+                                .or_else(|| payload.downcast_ref::<String>().cloned())
+                                .unwrap_or_else(|| "panicked".to_string())
+                        });
+                (implementation.name, outcome)
+            })
+            .collect();
+        silence_panics(false);
+        outcomes
+    }
+
+    /// Runs every implementation on the input.
+    ///
+    /// # Returns
+    /// The input shrunk to a minimal counterexample if the implementations disagree.
+    pub fn check(&self, input: &str) -> Option<Counterexample> {
+        let outcomes = self.outcomes(input);
+        if !disagree(&outcomes) {
+            return None;
+        }
+
+        let input = self.shrink(input, &outcomes);
+        Some(Counterexample {
+            day: self.day,
+            part: self.part,
+            outcomes: self.outcomes(&input),
+            input,
+        })
+    }
+
+    /// Removes units of the input with delta debugging as long as the implementations
+    /// still disagree and the same implementations panic as on the original input.
+    ///
+    /// Requiring the same panics keeps the shrinking from turning a wrong answer into
+    /// malformed input that only some implementations reject.
+    fn shrink(&self, input: &str, outcomes: &[(&'static str, Outcome)]) -> String {
+        let panics = |outcomes: &[(&'static str, Outcome)]| -> Vec<bool> {
+            outcomes
+                .iter()
+                .map(|(_, outcome)| outcome.is_err())
+                .collect()
+        };
+        let original_panics = panics(outcomes);
+        let still_fails = |candidate: &str| {
+            let outcomes = self.outcomes(candidate);
+            disagree(&outcomes) && panics(&outcomes) == original_panics
+        };
+
+        let (header, mut units) = self.split(input);
+        let mut granularity = 2;
+        while units.len() >= 2 {
+            let chunk_len = units.len().div_ceil(granularity);
+            let reduced = (0..units.len()).step_by(chunk_len).find_map(|start| {
+                let mut complement = units.clone();
+                complement.drain(start..(start + chunk_len).min(units.len()));
+                still_fails(&self.join(&header, &complement)).then_some(complement)
+            });
+
+            match reduced {
+                Some(complement) => {
+                    units = complement;
+                    granularity = (granularity - 1).max(2);
+                }
+                None if granularity < units.len() => {
+                    granularity = (granularity * 2).min(units.len());
+                }
+                None => break,
+            }
+        }
+        self.join(&header, &units)
+    }
+
+    fn split(&self, input: &str) -> (Option<String>, Vec<String>) {
+        match self.shrink {
+            Shrink::Lines => (None, input.lines().map(str::to_string).collect()),
+            Shrink::Items => (
+                None,
+                input.trim_end().split(',').map(str::to_string).collect(),
+            ),
+            Shrink::RowPairs => {
+                let mut lines = input.lines();
+                let header = lines.next().map(str::to_string);
+                let rows: Vec<&str> = lines.collect();
+                (header, rows.chunks(2).map(|pair| pair.join("\n")).collect())
+            }
+        }
+    }
+
+    fn join(&self, header: &Option<String>, units: &[String]) -> String {
+        let separator = match self.shrink {
+            Shrink::Items => ",",
+            Shrink::Lines | Shrink::RowPairs => "\n",
+        };
+        header
+            .iter()
+            .chain(units)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(separator)
+    }
+}
+
+thread_local! {
+    static PANICS_SILENCED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Keeps the panic hook from printing the panics caught on this thread, shrinking causes a lot of them.
+///
+/// The hook is wrapped once instead of being swapped on every call,
+/// as swapping it would race with panics on other threads.
+fn silence_panics(silenced: bool) {
+    static WRAP_HOOK: Once = Once::new();
+    WRAP_HOOK.call_once(|| {
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !PANICS_SILENCED.with(Cell::get) {
+                hook(info);
+            }
+        }));
+    });
+    PANICS_SILENCED.with(|cell| cell.set(silenced));
+}
+
+fn disagree(outcomes: &[(&'static str, Outcome)]) -> bool {
+    outcomes.windows(2).any(|pair| pair[0].1 != pair[1].1)
+}
+
+/// Runs every registered part on generated inputs.
+///
+/// # Returns
+/// The shrunk first counterexample of every part whose implementations disagree.
+///
+/// # Errors
+/// Fails if an input can't be generated.
+pub fn find_counterexamples(
+    options: &DifferentialOptions,
+) -> Result<Vec<Counterexample>, Box<dyn Error>> {
+    let mut counterexamples = Vec::new();
+    for part in registered_parts()
+        .into_iter()
+        .filter(|part| options.day.is_none_or(|day| day == part.day))
+    {
+        for seed in options.seed..options.seed + options.runs {
+            let generator = GeneratorOptions {
+                seed,
+                size: options.size,
+                width: None,
+            };
+            if let Some(counterexample) = part.check(&generate_input(part.day, &generator)?) {
+                counterexamples.push(counterexample);
+                break;
+            }
+        }
+    }
+    Ok(counterexamples)
+}
+
+/// Writes the input of a counterexample to a file like `day06-part2-<hash>.txt` in `directory`.
+///
+/// The name tells [`check_regressions`] which part to run the file with. The hash only depends
+/// on the input, so saving the same counterexample again overwrites its file.
+pub fn save_regression(
+    directory: &Path,
+    counterexample: &Counterexample,
+) -> Result<PathBuf, Box<dyn Error>> {
+    let path = directory.join(format!(
+        "day{:02}-part{}-{:016x}.txt",
+        counterexample.day,
+        counterexample.part,
+        fnv1a(counterexample.input.as_bytes())
+    ));
+
+    fs::create_dir_all(directory)?;
+    fs::write(&path, &counterexample.input)?;
+    Ok(path)
+}
+
+/// Runs every regression file saved by [`save_regression`] in `directory` with its part.
+///
+/// # Returns
+/// The counterexamples that still make the implementations disagree.
+///
+/// # Errors
+/// Fails if the directory can't be read or a file name doesn't name a registered part.
+pub fn check_regressions(directory: &Path) -> Result<Vec<Counterexample>, Box<dyn Error>> {
+    let parts = registered_parts();
+    let mut counterexamples = Vec::new();
+
+    let mut paths: Vec<PathBuf> = fs::read_dir(directory)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<_, _>>()?;
+    paths.retain(|path| path.extension().is_some_and(|extension| extension == "txt"));
+    paths.sort();
+
+    for path in paths {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let (day, part) = parse_regression_name(&name)
+            .ok_or_else(|| format!("'{}' is not named like day06-part2-<hash>.txt", name))?;
+        let part = parts
+            .iter()
+            .find(|registered| registered.day == day && registered.part == part)
+            .ok_or_else(|| {
+                format!(
+                    "'{}': Day {} part {} has no alternative implementations",
+                    name, day, part
+                )
+            })?;
+
+        let input = fs::read_to_string(&path)?;
+        let outcomes = part.outcomes(&input);
+        if disagree(&outcomes) {
+            counterexamples.push(Counterexample {
+                day: part.day,
+                part: part.part,
+                input,
+                outcomes,
+            });
+        }
+    }
+    Ok(counterexamples)
+}
+
+/// The 64-bit FNV-1a hash, which unlike `DefaultHasher` is the same on every Rust release.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+fn parse_regression_name(name: &str) -> Option<(u32, u32)> {
+    let mut fields = name.strip_prefix("day")?.split('-');
+    let day = fields.next()?.parse().ok()?;
+    let part = fields.next()?.strip_prefix("part")?.parse().ok()?;
+    Some((day, part))
+}
+
+impl Display for Counterexample {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Day {} part {}: The implementations disagree on",
+            self.day, self.part
+        )?;
+        for line in self.input.lines() {
+            writeln!(f, "    {}", line)?;
+        }
+        for (name, outcome) in &self.outcomes {
+            match outcome {
+                Ok(answer) => writeln!(f, "  {}: {}", name, answer)?,
+                Err(message) => writeln!(f, "  {}: panicked with '{}'", name, message)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registered_implementations_agree() {
+        for day in 1..=8 {
+            let options = DifferentialOptions {
+                day: Some(day),
+                seed: 0,
+                runs: 5,
+                size: 12,
+            };
+            let counterexamples = find_counterexamples(&options).unwrap();
+            assert!(counterexamples.is_empty(), "{}", counterexamples[0]);
+        }
+    }
+
+    #[test]
+    fn test_counterexample_is_shrunk() {
+        let part = Part::new(
+            0,
+            1,
+            Shrink::Lines,
+            vec![
+                implementation!("count", count_lines),
+                implementation!("count_without_sevens", count_lines_without_sevens),
+            ],
+        );
+        let input = (1..=10)
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let counterexample = part.check(&input).unwrap();
+        assert_eq!(counterexample.input, "7");
+        assert_eq!(
+            counterexample.outcomes,
+            vec![
+                ("count", Ok("1".to_string())),
+                ("count_without_sevens", Ok("0".to_string()))
+            ]
+        );
+        assert!(part.check("1\n2\n3").is_none());
+    }
+
+    #[test]
+    fn test_shrinking_keeps_panics() {
+        let part = Part::new(
+            0,
+            1,
+            Shrink::Items,
+            vec![
+                implementation!("count", count_items),
+                implementation!("count_after_source", count_items_after_source),
+            ],
+        );
+
+        // Without the source the second implementation panics, which would also be a disagreement
+        let counterexample = part.check("S,1,7,2").unwrap();
+        assert_eq!(counterexample.input, "S,7");
+    }
+
+    #[test]
+    fn test_regressions_round_trip() {
+        let directory = std::env::temp_dir().join(format!(
+            "adventofcode2025-regressions-{}",
+            std::process::id()
+        ));
+        let counterexample = Counterexample {
+            day: 6,
+            part: 2,
+            input: "12 3\n4  5\n*  +".to_string(),
+            outcomes: Vec::new(),
+        };
+
+        let path = save_regression(&directory, &counterexample).unwrap();
+        assert_eq!(
+            path.file_name().unwrap().to_string_lossy(),
+            "day06-part2-22b69174d99815b9.txt"
+        );
+        assert!(check_regressions(&directory).unwrap().is_empty());
+
+        fs::write(directory.join("notes.txt"), "").unwrap();
+        assert!(check_regressions(&directory).is_err());
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_checked_in_regressions_agree() {
+        let counterexamples = check_regressions(Path::new("tests/regressions")).unwrap();
+        assert!(counterexamples.is_empty(), "{}", counterexamples[0]);
+    }
+
+    fn count_lines(input: &str) -> usize {
+        input.lines().count()
+    }
+
+    fn count_lines_without_sevens(input: &str) -> usize {
+        input.lines().filter(|line| !line.contains('7')).count()
+    }
+
+    fn count_items(input: &str) -> usize {
+        input.split(',').count()
+    }
+
+    fn count_items_after_source(input: &str) -> usize {
+        let items = input.strip_prefix("S,").expect("Missing source");
+        1 + items.split(',').filter(|item| *item != "7").count()
+    }
+}
@@ -0,0 +1,343 @@
+use std::error::Error;
+
+use crate::{Rng, day08, load_input};
+
+/// The parameters of a generated input.
+///
+/// What `size` and `width` control depends on the day:
+///
+/// | Day | `size`                  | `width` (default)            |
+/// |-----|-------------------------|------------------------------|
+/// | 1   | dial instructions       | largest rotation (999)       |
+/// | 2   | ID ranges               | largest range length (1000)  |
+/// | 3   | battery banks           | batteries per bank (100)     |
+/// | 4   | grid rows               | grid columns (`size`)        |
+/// | 5   | ranges and IDs each     | largest range length (10⁹)   |
+/// | 6   | problems                | number rows (4)              |
+/// | 7   | manifold rows           | manifold columns (`size`)    |
+/// | 8   | junction boxes          | largest coordinate (100000)  |
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GeneratorOptions {
+    pub seed: u64,
+    pub size: usize,
+    pub width: Option<usize>,
+}
+
+/// Generates a random input in the format of the given day.
+///
+/// # Errors
+/// Fails if there is no generator for the day, the width is zero or larger than the day's
+/// parser accepts, or the size of a grid is zero.
+pub fn generate_input(day: u32, options: &GeneratorOptions) -> Result<String, Box<dyn Error>> {
+    if options.width == Some(0) {
+        return Err("The width of a generated input must be at least 1".into());
+    }
+    let max_width = match day {
+        1 => i32::MAX as usize,
+        8 => day08::MAX_COORDINATE as usize,
+        _ => usize::MAX,
+    };
+    if let Some(width) = options.width.filter(|&width| width > max_width) {
+        return Err(format!(
+            "The width of a generated Day {} input must be at most {}, got {}",
+            day, max_width, width
+        )
+        .into());
+    }
+    if matches!(day, 4 | 7) && options.size == 0 {
+        return Err(format!("A generated Day {} grid needs at least one row", day).into());
+    }
+
+    let mut rng = Rng::new(options.seed);
+    let size = options.size;
+    let width = |default: usize| options.width.unwrap_or(default);
+
+    let input = match day {
+        1 => dial_instructions(&mut rng, size, width(999)),
+        2 => id_ranges(&mut rng, size, width(1000)),
+        3 => battery_banks(&mut rng, size, width(100)),
+        4 => roll_grid(&mut rng, size, width(size)),
+        5 => ranges_and_ids(&mut rng, size, width(1_000_000_000)),
+        6 => worksheet(&mut rng, size, width(4)),
+        7 => manifold(&mut rng, size, width(size)),
+        8 => junction_boxes(&mut rng, size, width(100_000)),
+        _ => return Err(format!("There is no input generator for Day {}", day).into()),
+    };
+    Ok(input)
+}
+
+/// Loads the puzzle input of a day, or generates an input of about the same size if there is none.
+///
+/// This lets the benchmarks run on a checkout without puzzle inputs.
+///
+/// # Errors
+/// Fails if there is neither an input nor a generator for the day.
+pub fn load_or_generate_input(day: u32) -> Result<String, Box<dyn Error>> {
+    if let Ok(input) = load_input(day) {
+        return Ok(input);
+    }
+
+    let size = match day {
+        1 => 4500,
+        2 => 35,
+        3 => 200,
+        4 => 140,
+        5 => 190,
+        6 => 1000,
+        7 => 142,
+        _ => 1000,
+    };
+    let options = GeneratorOptions {
+        seed: 0,
+        size,
+        width: None,
+    };
+    generate_input(day, &options)
+}
+
+/// Rotations like `L68` or `R48`, one per line.
+fn dial_instructions(rng: &mut Rng, count: usize, max_rotation: usize) -> String {
+    lines((0..count).map(|_| {
+        let direction = if rng.chance(1, 2) { 'L' } else { 'R' };
+        format!("{}{}", direction, rng.range(1..=max_rotation as u64))
+    }))
+}
+
+/// Comma separated ranges like `11-22` on a single line.
+///
+/// The ranges are kept short, as they are searched ID by ID, but their IDs have up to ten digits.
+fn id_ranges(rng: &mut Rng, count: usize, max_length: usize) -> String {
+    (0..count)
+        .map(|_| {
+            let digits = rng.range(1..=10) as u32;
+            let start = rng.range(10u64.pow(digits - 1)..=10u64.pow(digits) - 1);
+            let end = start + rng.range(0..=max_length as u64 - 1);
+            format!("{}-{}", start, end)
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Lines of battery joltages from 1 to 9.
+fn battery_banks(rng: &mut Rng, count: usize, batteries: usize) -> String {
+    lines((0..count).map(|_| {
+        (0..batteries)
+            .map(|_| char::from(b'0' + rng.range(1..=9) as u8))
+            .collect::<String>()
+    }))
+}
+
+/// A grid where about two thirds of the cells hold a roll of paper `@`.
+fn roll_grid(rng: &mut Rng, rows: usize, columns: usize) -> String {
+    lines((0..rows).map(|_| {
+        (0..columns)
+            .map(|_| if rng.chance(2, 3) { '@' } else { '.' })
+            .collect::<String>()
+    }))
+}
+
+/// Fresh ID ranges, a blank line and the available IDs.
+fn ranges_and_ids(rng: &mut Rng, count: usize, max_length: usize) -> String {
+    const MAX_ID: u64 = 999_999_999_999_999;
+
+    let ranges = lines((0..count).map(|_| {
+        let start = rng.range(1..=MAX_ID);
+        let end = start.saturating_add(rng.range(0..=max_length as u64 - 1));
+        format!("{}-{}", start, end.min(MAX_ID))
+    }));
+    let ids = lines((0..count).map(|_| rng.range(1..=MAX_ID).to_string()));
+    format!("{}\n\n{}", ranges, ids)
+}
+
+/// Problems side by side, separated by a blank column, with the operator below each one.
+///
+/// The numbers of a problem are aligned to either side of its column, so reading them
+/// column-wise for part 2 gives different operands than reading them row-wise.
+fn worksheet(rng: &mut Rng, count: usize, number_rows: usize) -> String {
+    let mut rows = vec![String::new(); number_rows + 1];
+
+    for problem in 0..count {
+        // Monotonic lengths keep the padding of every column at its top or bottom,
+        // a gap between two digits wouldn't be a valid column-wise operand
+        let mut lengths: Vec<u32> = (0..number_rows).map(|_| rng.range(1..=4) as u32).collect();
+        lengths.sort_unstable();
+        if rng.chance(1, 2) {
+            lengths.reverse();
+        }
+        let numbers: Vec<String> = lengths
+            .into_iter()
+            .map(|digits| {
+                rng.range(10u64.pow(digits - 1)..=10u64.pow(digits) - 1)
+                    .to_string()
+            })
+            .collect();
+        let width = numbers.iter().map(String::len).max().unwrap_or(1);
+        let align_right = rng.chance(1, 2);
+        let operator = *rng.choose(&['+', '*']);
+
+        for (row, number) in rows.iter_mut().zip(&numbers) {
+            if problem > 0 {
+                row.push(' ');
+            }
+            if align_right {
+                row.push_str(&format!("{:>width$}", number));
+            } else {
+                row.push_str(&format!("{:<width$}", number));
+            }
+        }
+        let operator_row = &mut rows[number_rows];
+        if problem > 0 {
+            operator_row.push(' ');
+        }
+        operator_row.push_str(&format!("{:<width$}", operator));
+    }
+
+    lines(rows.into_iter())
+}
+
+/// A manifold in the puzzle layout: the source in the middle of the first row and
+/// splitters on every second row, never next to each other or in the outermost columns.
+fn manifold(rng: &mut Rng, rows: usize, columns: usize) -> String {
+    lines((0..rows).map(|y| {
+        let mut row = String::with_capacity(columns);
+        for x in 0..columns {
+            let splitter = y % 2 == 0
+                && y > 0
+                && x > 0
+                && x + 1 < columns
+                && !row.ends_with('^')
+                && rng.chance(1, 4);
+            row.push(if y == 0 && x == columns / 2 {
+                'S'
+            } else if splitter {
+                '^'
+            } else {
+                '.'
+            });
+        }
+        row
+    }))
+}
+
+/// Junction boxes at random 3D positions, one per line.
+fn junction_boxes(rng: &mut Rng, count: usize, max_coordinate: usize) -> String {
+    let max_coordinate = max_coordinate as u64;
+    lines((0..count).map(|_| {
+        format!(
+            "{},{},{}",
+            rng.range(0..=max_coordinate),
+            rng.range(0..=max_coordinate),
+            rng.range(0..=max_coordinate)
+        )
+    }))
+}
+
+fn lines(lines: impl Iterator<Item = String>) -> String {
+    lines.collect::<Vec<_>>().join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{day01, day02, day03, day04, day05, day06, day07, day08};
+
+    fn generate(day: u32, size: usize) -> String {
+        let options = GeneratorOptions {
+            seed: 2025,
+            size,
+            width: None,
+        };
+        generate_input(day, &options).unwrap()
+    }
+
+    #[test]
+    fn test_generated_inputs_can_be_solved() {
+        day01::solve_part1(&generate(1, 200));
+        day01::solve_part2(&generate(1, 200));
+        day02::solve_part1(&generate(2, 20));
+        day02::solve_part2(&generate(2, 20));
+        day03::solve_part1(&generate(3, 50));
+        day03::solve_part2(&generate(3, 50));
+        assert_eq!(
+            day04::solve_part2(&generate(4, 40)),
+            day04::solve_part2_with_bitset(&generate(4, 40))
+        );
+        day05::solve_part1(&generate(5, 100));
+        day05::solve_part2(&generate(5, 100));
+        day06::solve_part1(&generate(6, 50));
+        assert_eq!(
+            day06::solve_part2(&generate(6, 50)),
+            day06::solve_part2_with_transpose(&generate(6, 50))
+        );
+        assert_eq!(
+            day07::solve_part2(&generate(7, 60)).to_string(),
+            day07::solve_part2_with_hash_set(&generate(7, 60)).to_string()
+        );
+        assert_eq!(
+            day08::solve_part2(&generate(8, 300)),
+            day08::solve_part2_with_heap(&generate(8, 300))
+        );
+    }
+
+    #[test]
+    fn test_generated_inputs_are_deterministic() {
+        for day in 1..=8 {
+            assert_eq!(generate(day, 10), generate(day, 10));
+        }
+        let other_seed = GeneratorOptions {
+            seed: 1,
+            size: 10,
+            width: None,
+        };
+        assert_ne!(generate(8, 10), generate_input(8, &other_seed).unwrap());
+    }
+
+    #[test]
+    fn test_generated_formats() {
+        let options = GeneratorOptions {
+            seed: 3,
+            size: 3,
+            width: Some(5),
+        };
+        let grid = generate_input(4, &options).unwrap();
+        assert!(grid.lines().all(|line| line.len() == 5));
+        assert_eq!(grid.lines().count(), 3);
+
+        let worksheet = generate_input(6, &options).unwrap();
+        assert_eq!(worksheet.lines().count(), 6);
+        assert_eq!(
+            worksheet.lines().last().unwrap().split_whitespace().count(),
+            3
+        );
+
+        let ranges = generate_input(5, &options).unwrap();
+        assert_eq!(ranges.split("\n\n").count(), 2);
+
+        assert!(generate_input(12, &options).is_err());
+        let zero_width = GeneratorOptions {
+            width: Some(0),
+            ..options
+        };
+        assert!(generate_input(1, &zero_width).is_err());
+
+        let empty_grid = GeneratorOptions { size: 0, ..options };
+        assert!(generate_input(4, &empty_grid).is_err());
+        assert!(generate_input(7, &empty_grid).is_err());
+        assert_eq!(generate_input(1, &empty_grid).unwrap(), "");
+
+        let widest = GeneratorOptions {
+            width: Some(day08::MAX_COORDINATE as usize),
+            ..options
+        };
+        assert!(day08::try_solve_part2(&generate_input(8, &widest).unwrap()).is_ok());
+        let too_wide = GeneratorOptions {
+            width: Some(day08::MAX_COORDINATE as usize + 1),
+            ..options
+        };
+        assert_eq!(
+            generate_input(8, &too_wide).unwrap_err().to_string(),
+            "The width of a generated Day 8 input must be at most 16777216, got 16777217"
+        );
+    }
+}
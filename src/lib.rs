@@ -1,70 +1,374 @@
 mod utils;
-use std::error::Error;
+use std::{error::Error, fs, path::PathBuf};
 
 pub use utils::*;
 
 mod days;
 pub use days::*;
 
-use clap::{Parser, command};
+mod differential;
+pub use differential::*;
+
+mod generate;
+pub use generate::*;
+
+use clap::{Parser, Subcommand};
 
 // A simple command-line application for the Advent of Code 2025
 #[derive(Parser)]
 #[command(name = "Advent of Code 2025")]
 #[command(about = "Run solutions for Advent of Code 2025", long_about = None)]
 #[command(author = "Markus Fruhmann")]
+#[command(subcommand_negates_reqs = true)]
 pub struct CliArgs {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     // The day that should be executed (1-12)
-    #[arg(short, long)]
-    day: u32,
+    #[arg(short, long, required = true)]
+    day: Option<u32>,
+
+    // Print diagnostic details on how the solution was derived (days 5, 6 and 8)
+    #[arg(long)]
+    explain: bool,
+
+    // The order in which the operands of a day 6 problem are combined
+    // (left-to-right or right-to-left)
+    #[arg(long, default_value_t = day06::EvaluationOrder::LeftToRight)]
+    order: day06::EvaluationOrder,
+
+    // The number of columns a tab advances to in a day 6 worksheet
+    #[arg(long, default_value_t = day06::DEFAULT_TAB_WIDTH)]
+    tab_width: usize,
+
+    // Report the day 7 timeline count modulo this number, usually a large prime
+    #[arg(long)]
+    modulus: Option<u64>,
+
+    // Draw the day 7 manifold with its beam paths in the terminal
+    #[arg(long)]
+    render: bool,
+
+    // Write the day 7 manifold with its beam paths to this SVG file
+    #[arg(long)]
+    svg: Option<PathBuf>,
+
+    // Write the day 8 circuits after the part 1 connections to this Graphviz DOT file
+    #[arg(long)]
+    dot: Option<PathBuf>,
+
+    // Write the day 8 circuits after the part 1 connections to this JSON file
+    #[arg(long)]
+    json: Option<PathBuf>,
+
+    // The number of shortest connections made in day 8 part 1
+    #[arg(long, default_value_t = 1000)]
+    connections: usize,
+
+    // The number of largest circuits whose sizes are multiplied in day 8 part 1
+    #[arg(long, default_value_t = 3)]
+    largest: usize,
+
+    // The metric measuring the distance between two junction boxes in day 8
+    // (euclidean, manhattan or chebyshev)
+    #[arg(long, default_value_t = Metric::Euclidean)]
+    metric: Metric,
+
+    // Ask when something happens while the day 8 connections are made, can be repeated
+    // ('connected A B', 'exceeds S' or 'last-merge')
+    #[arg(long = "what-if")]
+    what_if: Vec<day08::Query>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    // Print a random input in the format of a day to stdout
+    Generate {
+        // The day whose input format is generated (1-8)
+        #[arg(short, long)]
+        day: u32,
+
+        // The seed of the generator, the same seed always gives the same input
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+
+        // The number of lines, rows or items, depending on the day
+        #[arg(long, default_value_t = 100)]
+        size: usize,
+
+        // The second size parameter of a day, like the number of columns or the largest value
+        #[arg(long)]
+        width: Option<usize>,
+    },
+    // Run every alternative implementation on generated inputs and report where they disagree
+    Compare {
+        // Only compare the implementations of this day
+        #[arg(short, long)]
+        day: Option<u32>,
+
+        // The seed of the first generated input
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+
+        // The number of generated inputs per part
+        #[arg(long, default_value_t = 100)]
+        runs: u64,
+
+        // The size of the generated inputs, small inputs make readable counterexamples
+        #[arg(long, default_value_t = 20)]
+        size: usize,
+
+        // Save the shrunk counterexamples as regression files in this directory
+        #[arg(long)]
+        save: Option<PathBuf>,
+    },
 }
 
 pub fn run(args: CliArgs) -> Result<(), Box<dyn Error>> {
-    let input = load_input(args.day)?;
+    match args.command {
+        Some(Command::Generate {
+            day,
+            seed,
+            size,
+            width,
+        }) => {
+            let options = GeneratorOptions { seed, size, width };
+            println!("{}", generate_input(day, &options)?);
+            return Ok(());
+        }
+        Some(Command::Compare {
+            day,
+            seed,
+            runs,
+            size,
+            save,
+        }) => {
+            let options = DifferentialOptions {
+                day,
+                seed,
+                runs,
+                size,
+            };
+            return compare(&options, save);
+        }
+        None => {}
+    }
+
+    let day = args.day.ok_or("A day is required")?;
+    let input = load_input(day)?;
+    let circuit_options = day08::CircuitOptions {
+        connections: args.connections,
+        largest: args.largest,
+        metric: args.metric,
+    };
+    let worksheet_options = day06::WorksheetOptions {
+        order: args.order,
+        tab_width: args.tab_width,
+    };
 
-    println!("The solutions for day {} are:", args.day);
-    match args.day {
+    println!("The solutions for day {} are:", day);
+    match day {
         1 => println!(
             "Part 1: {}\nPart 2: {}",
-            day01::solve_part1(input.as_str()),
-            day01::solve_part2(input.as_str())
+            day01::try_solve_part1(input.as_str())?,
+            day01::try_solve_part2(input.as_str())?
         ),
         2 => println!(
             "Part 1: {}\nPart 2: {}",
-            day02::solve_part1(input.as_str()),
-            day02::solve_part2(input.as_str())
+            day02::try_solve_part1(input.as_str())?,
+            day02::try_solve_part2(input.as_str())?
         ),
         3 => println!(
             "Part 1: {}\nPart 2: {}",
-            day03::solve_part1(input.as_str()),
-            day03::solve_part2(input.as_str())
+            day03::try_solve_part1(input.as_str())?,
+            day03::try_solve_part2(input.as_str())?
         ),
         4 => println!(
             "Part 1: {}\nPart 2: {}",
-            day04::solve_part1(input.as_str()),
-            day04::solve_part2(input.as_str())
+            day04::try_solve_part1(input.as_str())?,
+            day04::try_solve_part2(input.as_str())?
         ),
         5 => println!(
             "Part 1: {}\nPart 2: {}",
-            day05::solve_part1(input.as_str()),
-            day05::solve_part2(input.as_str())
+            day05::try_solve_part1(input.as_str())?,
+            day05::try_solve_part2(input.as_str())?
         ),
         6 => println!(
             "Part 1: {}\nPart 2: {}",
-            day06::solve_part1(input.as_str()),
-            day06::solve_part2(input.as_str())
+            day06::solve_with_options(
+                input.as_str(),
+                day06::ReadingMode::RowWise,
+                &worksheet_options
+            )?,
+            day06::solve_with_options(
+                input.as_str(),
+                day06::ReadingMode::ColumnWise,
+                &worksheet_options
+            )?
         ),
         7 => println!(
-            "Part 1: {}\nPart 2: {}",
-            day07::solve_part1(input.as_str()),
-            day07::solve_part2(input.as_str())
+            "Part 1: {}\nPart 2: {}\nTimelines leaving through the sides: {}",
+            day07::try_solve_part1(input.as_str())?,
+            match args.modulus {
+                Some(modulus) => format!(
+                    "{} (mod {})",
+                    day07::count_timelines_modulo(input.as_str(), modulus)?,
+                    modulus
+                ),
+                None => day07::try_solve_part2(input.as_str())?.to_string(),
+            },
+            day07::count_exits(input.as_str(), &day07::CellTable::puzzle())
         ),
         8 => println!(
             "Part 1: {}\nPart 2: {}",
-            day08::solve_part1(input.as_str()),
-            day08::solve_part2(input.as_str())
+            day08::solve_part1_with_options(input.as_str(), &circuit_options)?,
+            day08::solve_part2_with_metric(input.as_str(), args.metric)?
         ),
-        _ => println!("Solution for Day {} is not yet implemented.", args.day),
+        _ => println!("Solution for Day {} is not yet implemented.", day),
+    }
+
+    if args.explain {
+        explain(day, input.as_str(), &worksheet_options, &circuit_options)?;
+    }
+    if args.render || args.svg.is_some() {
+        render(day, input.as_str(), args.render, args.svg)?;
+    }
+    if !args.what_if.is_empty() {
+        what_if(day, input.as_str(), args.metric, &args.what_if)?;
+    }
+    if args.dot.is_some() || args.json.is_some() {
+        export(day, input.as_str(), &circuit_options, args.dot, args.json)?;
+    }
+    Ok(())
+}
+
+fn compare(options: &DifferentialOptions, save: Option<PathBuf>) -> Result<(), Box<dyn Error>> {
+    let counterexamples = find_counterexamples(options)?;
+    for counterexample in &counterexamples {
+        print!("{}", counterexample);
+        if let Some(directory) = &save {
+            let path = save_regression(directory, counterexample)?;
+            println!("Saved the counterexample to {}", path.display());
+        }
+    }
+
+    if !counterexamples.is_empty() {
+        return Err(format!(
+            "The implementations of {} part(s) disagree",
+            counterexamples.len()
+        )
+        .into());
+    }
+    println!(
+        "All implementations agree on {} generated input(s) per part",
+        options.runs
+    );
+    Ok(())
+}
+
+fn explain(
+    day: u32,
+    input: &str,
+    worksheet_options: &day06::WorksheetOptions,
+    circuit_options: &day08::CircuitOptions,
+) -> Result<(), Box<dyn Error>> {
+    match day {
+        5 => {
+            for diagnostic in day05::diagnose_ids::<u64>(input)? {
+                println!("{}", diagnostic);
+            }
+        }
+        6 => {
+            println!("Part 1:");
+            print!(
+                "{}",
+                day06::explain(input, day06::ReadingMode::RowWise, worksheet_options)?
+            );
+            println!("Part 2:");
+            print!(
+                "{}",
+                day06::explain(input, day06::ReadingMode::ColumnWise, worksheet_options)?
+            );
+        }
+        8 => {
+            let network = day08::analyze_network(input, circuit_options)?;
+            for (number, circuit) in network.circuits.iter().enumerate() {
+                if circuit.junction_boxes.len() > 1 {
+                    println!(
+                        "Circuit {}: {} junction boxes, {} connections, cable length {:.2}",
+                        number,
+                        circuit.junction_boxes.len(),
+                        circuit.connections.len(),
+                        circuit.cable_length
+                    );
+                }
+            }
+            for (size, count) in network.size_histogram() {
+                println!("Circuits of size {}: {}", size, count);
+            }
+        }
+        _ => println!("Explain mode is not available for Day {}.", day),
+    }
+    Ok(())
+}
+
+fn render(day: u32, input: &str, ansi: bool, svg: Option<PathBuf>) -> Result<(), Box<dyn Error>> {
+    if day != 7 {
+        println!("Rendering is not available for Day {}.", day);
+        return Ok(());
+    }
+
+    if ansi {
+        print!("{}", day07::render_ansi(input));
+    }
+    if let Some(path) = svg {
+        fs::write(&path, day07::render_svg(input))?;
+        println!("Wrote the rendered manifold to {}", path.display());
+    }
+    Ok(())
+}
+
+fn export(
+    day: u32,
+    input: &str,
+    circuit_options: &day08::CircuitOptions,
+    dot: Option<PathBuf>,
+    json: Option<PathBuf>,
+) -> Result<(), Box<dyn Error>> {
+    if day != 8 {
+        println!("Exporting is not available for Day {}.", day);
+        return Ok(());
+    }
+
+    let network = day08::analyze_network(input, circuit_options)?;
+    if let Some(path) = dot {
+        fs::write(&path, network.to_dot())?;
+        println!("Wrote the network to {}", path.display());
+    }
+    if let Some(path) = json {
+        fs::write(&path, network.to_json())?;
+        println!("Wrote the network to {}", path.display());
+    }
+    Ok(())
+}
+
+fn what_if(
+    day: u32,
+    input: &str,
+    metric: Metric,
+    queries: &[day08::Query],
+) -> Result<(), Box<dyn Error>> {
+    if day != 8 {
+        println!("What-if queries are not available for Day {}.", day);
+        return Ok(());
+    }
+
+    let answers = day08::answer_queries(input, metric, queries)?;
+    for (query, answer) in queries.iter().zip(answers) {
+        match answer {
+            Some(answer) => println!("{}: {}", query, answer),
+            None => println!("{}: never", query),
+        }
     }
     Ok(())
 }
@@ -1,2 +1,12 @@
+mod interval_set;
+mod kd_tree;
 mod load_input;
+mod parse_error;
+mod rng;
+mod union_find;
+pub use interval_set::*;
+pub use kd_tree::*;
 pub use load_input::*;
+pub use parse_error::*;
+pub use rng::*;
+pub use union_find::*;
@@ -0,0 +1,317 @@
+use std::{fmt::Debug, ops::Sub};
+
+/// Unsigned integer types that can be used as the bounds of an [`IntervalSet`].
+pub trait IntervalBound: Copy + Ord + Debug + Sub<Output = Self> {
+    const MIN: Self;
+    const MAX: Self;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_succ(self) -> Option<Self>;
+    fn checked_pred(self) -> Option<Self>;
+}
+
+macro_rules! impl_interval_bound {
+    ($($t:ty),*) => {
+        $(
+            impl IntervalBound for $t {
+                const MIN: Self = <$t>::MIN;
+                const MAX: Self = <$t>::MAX;
+
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    <$t>::checked_add(self, other)
+                }
+
+                fn checked_succ(self) -> Option<Self> {
+                    self.checked_add(1)
+                }
+
+                fn checked_pred(self) -> Option<Self> {
+                    self.checked_sub(1)
+                }
+            }
+        )*
+    };
+}
+
+impl_interval_bound!(u8, u16, u32, u64, u128, usize);
+
+/// A set of integers stored as sorted, disjoint and non-adjacent inclusive intervals.
+///
+/// Every insertion normalizes the set, so overlapping or touching intervals are merged
+/// and membership can be answered by a binary search over the intervals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntervalSet<T> {
+    intervals: Vec<(T, T)>,
+}
+
+impl<T: IntervalBound> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: IntervalBound> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self {
+            intervals: Vec::new(),
+        }
+    }
+
+    /// Creates a set from inclusive `start..=end` intervals.
+    /// Intervals with `start > end` are empty and ignored.
+    pub fn from_inclusive(intervals: impl IntoIterator<Item = (T, T)>) -> Self {
+        let mut intervals: Vec<(T, T)> = intervals
+            .into_iter()
+            .filter(|(start, end)| start <= end)
+            .collect();
+        intervals.sort_unstable();
+
+        let mut set = Self::new();
+        for (start, end) in intervals {
+            match set.intervals.last_mut() {
+                Some(last) if touches(last.1, start) => last.1 = last.1.max(end),
+                _ => set.intervals.push((start, end)),
+            }
+        }
+        set
+    }
+
+    /// Creates a set from half-open `start..end` intervals.
+    /// Intervals with `start >= end` are empty and ignored.
+    pub fn from_half_open(intervals: impl IntoIterator<Item = (T, T)>) -> Self {
+        Self::from_inclusive(
+            intervals
+                .into_iter()
+                .filter(|(start, end)| start < end)
+                .filter_map(|(start, end)| Some((start, end.checked_pred()?))),
+        )
+    }
+
+    /// Inserts the inclusive interval `start..=end`, merging it with every interval it overlaps or touches.
+    pub fn insert_inclusive(&mut self, start: T, end: T) {
+        if start > end {
+            return;
+        }
+
+        // First interval that ends at or after `start - 1` and could therefore be merged
+        let first = self
+            .intervals
+            .partition_point(|&(_, existing_end)| !touches(existing_end, start));
+        // First interval that starts after `end + 1` and stays untouched
+        let last = self
+            .intervals
+            .partition_point(|&(existing_start, _)| touches(end, existing_start));
+
+        let merged = if first < last {
+            (
+                start.min(self.intervals[first].0),
+                end.max(self.intervals[last - 1].1),
+            )
+        } else {
+            (start, end)
+        };
+        self.intervals.splice(first..last, [merged]);
+    }
+
+    /// Inserts the half-open interval `start..end`.
+    pub fn insert_half_open(&mut self, start: T, end: T) {
+        if start < end {
+            self.insert_inclusive(start, end.checked_pred().unwrap());
+        }
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        self.interval_containing(value).is_some()
+    }
+
+    /// Returns the inclusive interval that contains `value`, if any.
+    pub fn interval_containing(&self, value: T) -> Option<(T, T)> {
+        let index = self.intervals.partition_point(|&(_, end)| end < value);
+        self.intervals
+            .get(index)
+            .copied()
+            .filter(|&(start, _)| start <= value)
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self::from_inclusive(self.iter().chain(other.iter()))
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut intervals = Vec::new();
+        let (mut i, mut j) = (0, 0);
+
+        while i < self.intervals.len() && j < other.intervals.len() {
+            let (a_start, a_end) = self.intervals[i];
+            let (b_start, b_end) = other.intervals[j];
+
+            let start = a_start.max(b_start);
+            let end = a_end.min(b_end);
+            if start <= end {
+                intervals.push((start, end));
+            }
+
+            if a_end < b_end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        Self { intervals }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        self.intersection(&other.complement(T::MIN, T::MAX))
+    }
+
+    /// Returns every value within the inclusive bounds `lower..=upper` that is not part of the set.
+    pub fn complement(&self, lower: T, upper: T) -> Self {
+        let mut intervals = Vec::new();
+        if lower > upper {
+            return Self { intervals };
+        }
+
+        // Start of the next gap, `None` once the gap would start beyond `T::MAX`
+        let mut gap_start = Some(lower);
+        for &(start, end) in &self.intervals {
+            let Some(current) = gap_start else {
+                break;
+            };
+            if start > upper {
+                break;
+            }
+            if start > current {
+                intervals.push((current, start.checked_pred().unwrap()));
+            }
+            gap_start = end.checked_succ().map(|next| next.max(current));
+        }
+
+        if let Some(current) = gap_start
+            && current <= upper
+        {
+            intervals.push((current, upper));
+        }
+
+        Self { intervals }
+    }
+
+    /// Returns the number of values covered by the set.
+    ///
+    /// Since the intervals are disjoint the count can only exceed `T::MAX` if the set covers
+    /// every value of `T`, in which case `None` is returned.
+    pub fn covered_len(&self) -> Option<T> {
+        self.intervals
+            .iter()
+            .try_fold(T::MIN, |acc, &(start, end)| {
+                acc.checked_add((end - start).checked_succ()?)
+            })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// Iterates over the normalized inclusive intervals in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = (T, T)> + '_ {
+        self.intervals.iter().copied()
+    }
+}
+
+impl<T: IntervalBound> FromIterator<(T, T)> for IntervalSet<T> {
+    /// Collects inclusive intervals into a normalized set.
+    fn from_iter<I: IntoIterator<Item = (T, T)>>(iter: I) -> Self {
+        Self::from_inclusive(iter)
+    }
+}
+
+/// Checks whether an interval ending at `end` overlaps or touches an interval starting at `start`.
+fn touches<T: IntervalBound>(end: T, start: T) -> bool {
+    end.checked_succ().is_none_or(|next| start <= next)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_inclusive_normalizes() {
+        let set = IntervalSet::from_inclusive([(10u64, 14), (3, 5), (16, 20), (12, 18), (6, 6)]);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![(3, 6), (10, 20)]);
+        assert_eq!(set.covered_len(), Some(15));
+    }
+
+    #[test]
+    fn test_from_half_open() {
+        let set = IntervalSet::from_half_open([(0u64, 5), (5, 10), (20, 20)]);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![(0, 9)]);
+    }
+
+    #[test]
+    fn test_insert_inclusive() {
+        let mut set = IntervalSet::new();
+        set.insert_inclusive(10u64, 12);
+        set.insert_inclusive(1, 2);
+        set.insert_inclusive(20, 25);
+        set.insert_inclusive(4, 4);
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            vec![(1, 2), (4, 4), (10, 12), (20, 25)]
+        );
+
+        set.insert_inclusive(3, 19);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![(1, 25)]);
+    }
+
+    #[test]
+    fn test_contains() {
+        let set = IntervalSet::from_inclusive([(3u64, 5), (10, 20)]);
+        assert!(!set.contains(1));
+        assert!(set.contains(3));
+        assert!(set.contains(5));
+        assert!(!set.contains(8));
+        assert!(set.contains(17));
+        assert!(!set.contains(32));
+        assert_eq!(set.interval_containing(11), Some((10, 20)));
+    }
+
+    #[test]
+    fn test_set_algebra() {
+        let a = IntervalSet::from_inclusive([(0u64, 10), (20, 30)]);
+        let b = IntervalSet::from_inclusive([(5u64, 25)]);
+
+        assert_eq!(a.union(&b).iter().collect::<Vec<_>>(), vec![(0, 30)]);
+        assert_eq!(
+            a.intersection(&b).iter().collect::<Vec<_>>(),
+            vec![(5, 10), (20, 25)]
+        );
+        assert_eq!(
+            a.difference(&b).iter().collect::<Vec<_>>(),
+            vec![(0, 4), (26, 30)]
+        );
+        assert_eq!(
+            a.complement(0, 40).iter().collect::<Vec<_>>(),
+            vec![(11, 19), (31, 40)]
+        );
+    }
+
+    #[test]
+    fn test_bounds_of_numeric_domain() {
+        let set = IntervalSet::from_inclusive([(250u8, 255), (0, 3)]);
+        assert!(set.contains(u8::MAX));
+        assert_eq!(
+            set.complement(u8::MIN, u8::MAX).iter().collect::<Vec<_>>(),
+            vec![(4, 249)]
+        );
+
+        let mut set = IntervalSet::new();
+        set.insert_inclusive(u8::MAX, u8::MAX);
+        set.insert_inclusive(0, 254);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![(0, 255)]);
+        assert!(set.complement(u8::MIN, u8::MAX).is_empty());
+        assert_eq!(set.covered_len(), None);
+
+        let set = IntervalSet::from_inclusive([(1u8, 255)]);
+        assert_eq!(set.covered_len(), Some(255));
+    }
+}
@@ -0,0 +1,438 @@
+use std::{collections::BinaryHeap, fmt::Display, str::FromStr};
+
+/// A point with integer coordinates that can be stored in a [`KdTree`].
+pub trait Point {
+    fn dimensions(&self) -> usize;
+    fn coordinate(&self, axis: usize) -> i64;
+}
+
+impl<const D: usize> Point for [i64; D] {
+    fn dimensions(&self) -> usize {
+        D
+    }
+
+    fn coordinate(&self, axis: usize) -> i64 {
+        self[axis]
+    }
+}
+
+impl Point for Vec<i64> {
+    fn dimensions(&self) -> usize {
+        self.len()
+    }
+
+    fn coordinate(&self, axis: usize) -> i64 {
+        self[axis]
+    }
+}
+
+/// Returns the squared Euclidean distance between two points of the same dimension.
+pub fn squared_distance<P: Point + ?Sized, Q: Point + ?Sized>(a: &P, b: &Q) -> i64 {
+    Metric::Euclidean.distance(a, b)
+}
+
+/// The distance functions a [`KdTree`] can search with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Metric {
+    #[default]
+    Euclidean,
+    Manhattan,
+    Chebyshev,
+}
+
+impl Metric {
+    /// Returns an exact integer that orders pairs of points like their distance.
+    ///
+    /// This is the distance itself for Manhattan and Chebyshev, and the squared distance
+    /// for Euclidean, which avoids the rounding of a square root.
+    ///
+    /// # Panics
+    /// Panics if the distance doesn't fit into an `i64`. Coordinates within `±2^24`
+    /// in up to `2^12` dimensions are always safe.
+    pub fn distance<P: Point + ?Sized, Q: Point + ?Sized>(&self, a: &P, b: &Q) -> i64 {
+        let mut offsets = (0..a.dimensions()).map(|axis| {
+            a.coordinate(axis)
+                .checked_sub(b.coordinate(axis))
+                .and_then(i64::checked_abs)
+        });
+        let distance = match self {
+            Metric::Euclidean => {
+                offsets.try_fold(0i64, |sum, offset| sum.checked_add(offset?.checked_pow(2)?))
+            }
+            Metric::Manhattan => offsets.try_fold(0i64, |sum, offset| sum.checked_add(offset?)),
+            Metric::Chebyshev => offsets.try_fold(0i64, |max, offset| Some(max.max(offset?))),
+        };
+        distance.expect("The distance between two points overflows an i64")
+    }
+
+    /// Converts a value returned by [`Metric::distance`] into the actual distance.
+    pub fn length(&self, distance: i64) -> f64 {
+        match self {
+            Metric::Euclidean => (distance as f64).sqrt(),
+            Metric::Manhattan | Metric::Chebyshev => distance as f64,
+        }
+    }
+
+    /// Returns the smallest possible distance between two points whose coordinates
+    /// differ by `offset` along a single axis.
+    fn axis_bound(&self, offset: i64) -> i64 {
+        match self {
+            Metric::Euclidean => offset.pow(2),
+            Metric::Manhattan | Metric::Chebyshev => offset.abs(),
+        }
+    }
+}
+
+impl FromStr for Metric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "euclidean" => Ok(Metric::Euclidean),
+            "manhattan" => Ok(Metric::Manhattan),
+            "chebyshev" => Ok(Metric::Chebyshev),
+            _ => Err(format!(
+                "Unknown metric '{}', expected euclidean, manhattan or chebyshev",
+                s
+            )),
+        }
+    }
+}
+
+impl Display for Metric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Metric::Euclidean => write!(f, "euclidean"),
+            Metric::Manhattan => write!(f, "manhattan"),
+            Metric::Chebyshev => write!(f, "chebyshev"),
+        }
+    }
+}
+
+/// A point found by a [`KdTree`] query together with its distance to the query,
+/// as returned by [`Metric::distance`].
+///
+/// Neighbours are ordered by their distance and then by their index,
+/// so results are deterministic even if several points are equally far away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Neighbour {
+    pub distance: i64,
+    pub index: usize,
+}
+
+/// A static k-d tree over points of any dimension.
+///
+/// The tree is stored implicitly in a permutation of the point indices: the median of every
+/// range is the root of that range's subtree, split along the axis `depth % dimensions`.
+/// Building takes `O(n log n)` and the tree needs no memory besides the permutation.
+#[derive(Debug, Clone)]
+pub struct KdTree<P> {
+    points: Vec<P>,
+    order: Vec<usize>,
+    dimensions: usize,
+}
+
+impl<P: Point> KdTree<P> {
+    /// Builds a tree over the points, which are addressed by their index in `points` afterwards.
+    ///
+    /// # Panics
+    /// Panics if the points don't all have the same dimension, or if they have none.
+    pub fn new(points: Vec<P>) -> Self {
+        let dimensions = points.first().map_or(0, Point::dimensions);
+        assert!(
+            points.iter().all(|point| point.dimensions() == dimensions),
+            "All points of a k-d tree need the same dimension"
+        );
+        assert!(
+            points.is_empty() || dimensions > 0,
+            "The points of a k-d tree need at least one dimension"
+        );
+
+        let mut order: Vec<usize> = (0..points.len()).collect();
+        if dimensions > 0 {
+            build(&points, &mut order, 0, dimensions);
+        }
+        Self {
+            points,
+            order,
+            dimensions,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn point(&self, index: usize) -> &P {
+        &self.points[index]
+    }
+
+    pub fn points(&self) -> &[P] {
+        &self.points
+    }
+
+    /// Returns the `k` points closest to `query` by Euclidean distance, closest first.
+    pub fn nearest(&self, query: &impl Point, k: usize) -> Vec<Neighbour> {
+        self.nearest_by(query, k, Metric::Euclidean)
+    }
+
+    /// Returns the `k` points closest to `query` by the given metric, closest first.
+    pub fn nearest_by(&self, query: &impl Point, k: usize, metric: Metric) -> Vec<Neighbour> {
+        let mut best = BinaryHeap::with_capacity(k + 1);
+        if k > 0 {
+            self.search_nearest(&self.order, 0, query, k, metric, &mut best);
+        }
+        best.into_sorted_vec()
+    }
+
+    /// Returns the indices of all points whose squared distance to `query` is at most
+    /// `squared_radius`, in ascending order.
+    pub fn within_radius(&self, query: &impl Point, squared_radius: i64) -> Vec<usize> {
+        let mut found = Vec::new();
+        self.search_radius(&self.order, 0, query, squared_radius, &mut found);
+        found.sort_unstable();
+        found
+    }
+
+    /// Returns the indices of all points inside the axis-aligned box spanned by the inclusive
+    /// corners `min` and `max`, in ascending order.
+    pub fn within_box(&self, min: &impl Point, max: &impl Point) -> Vec<usize> {
+        let mut found = Vec::new();
+        self.search_box(&self.order, 0, min, max, &mut found);
+        found.sort_unstable();
+        found
+    }
+
+    fn search_nearest(
+        &self,
+        range: &[usize],
+        depth: usize,
+        query: &impl Point,
+        k: usize,
+        metric: Metric,
+        best: &mut BinaryHeap<Neighbour>,
+    ) {
+        if range.is_empty() {
+            return;
+        }
+
+        let mid = range.len() / 2;
+        let index = range[mid];
+        let point = &self.points[index];
+        let candidate = Neighbour {
+            distance: metric.distance(point, query),
+            index,
+        };
+        if best.len() < k {
+            best.push(candidate);
+        } else if let Some(mut worst) = best.peek_mut()
+            && candidate < *worst
+        {
+            *worst = candidate;
+        }
+
+        let axis = depth % self.dimensions;
+        let offset = query.coordinate(axis) - point.coordinate(axis);
+        let (near, far) = if offset < 0 {
+            (&range[..mid], &range[mid + 1..])
+        } else {
+            (&range[mid + 1..], &range[..mid])
+        };
+
+        self.search_nearest(near, depth + 1, query, k, metric, best);
+        // Ties with the worst neighbour can still win on their index, so only skip farther points
+        if best.len() < k
+            || best
+                .peek()
+                .is_some_and(|worst| metric.axis_bound(offset) <= worst.distance)
+        {
+            self.search_nearest(far, depth + 1, query, k, metric, best);
+        }
+    }
+
+    fn search_radius(
+        &self,
+        range: &[usize],
+        depth: usize,
+        query: &impl Point,
+        squared_radius: i64,
+        found: &mut Vec<usize>,
+    ) {
+        if range.is_empty() {
+            return;
+        }
+
+        let mid = range.len() / 2;
+        let index = range[mid];
+        let point = &self.points[index];
+        if squared_distance(point, query) <= squared_radius {
+            found.push(index);
+        }
+
+        let axis = depth % self.dimensions;
+        let offset = query.coordinate(axis) - point.coordinate(axis);
+        if offset <= 0 || offset.pow(2) <= squared_radius {
+            self.search_radius(&range[..mid], depth + 1, query, squared_radius, found);
+        }
+        if offset >= 0 || offset.pow(2) <= squared_radius {
+            self.search_radius(&range[mid + 1..], depth + 1, query, squared_radius, found);
+        }
+    }
+
+    fn search_box(
+        &self,
+        range: &[usize],
+        depth: usize,
+        min: &impl Point,
+        max: &impl Point,
+        found: &mut Vec<usize>,
+    ) {
+        if range.is_empty() {
+            return;
+        }
+
+        let mid = range.len() / 2;
+        let index = range[mid];
+        let point = &self.points[index];
+        if (0..self.dimensions).all(|axis| {
+            (min.coordinate(axis)..=max.coordinate(axis)).contains(&point.coordinate(axis))
+        }) {
+            found.push(index);
+        }
+
+        let axis = depth % self.dimensions;
+        if min.coordinate(axis) <= point.coordinate(axis) {
+            self.search_box(&range[..mid], depth + 1, min, max, found);
+        }
+        if max.coordinate(axis) >= point.coordinate(axis) {
+            self.search_box(&range[mid + 1..], depth + 1, min, max, found);
+        }
+    }
+}
+
+/// Arranges `order` so that the median along the current axis is in the middle,
+/// with smaller coordinates before it and larger ones after it, then recurses into both halves.
+fn build<P: Point>(points: &[P], order: &mut [usize], depth: usize, dimensions: usize) {
+    if order.len() <= 1 {
+        return;
+    }
+
+    let axis = depth % dimensions;
+    let mid = order.len() / 2;
+    order.select_nth_unstable_by_key(mid, |&index| points[index].coordinate(axis));
+
+    let (left, right) = order.split_at_mut(mid);
+    build(points, left, depth + 1, dimensions);
+    build(points, &mut right[1..], depth + 1, dimensions);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Rng;
+
+    /// Generates pseudo-random 3D points within `0..range` on every axis.
+    fn generate_points(count: usize, range: u64) -> Vec<[i64; 3]> {
+        let mut rng = Rng::new(0);
+        (0..count)
+            .map(|_| [(); 3].map(|_| rng.range(0..=range - 1) as i64))
+            .collect()
+    }
+
+    #[test]
+    fn test_nearest_matches_brute_force() {
+        // A small range produces plenty of equal distances and duplicate points
+        let points = generate_points(500, 20);
+        let tree = KdTree::new(points.clone());
+
+        for query in generate_points(50, 25) {
+            for metric in [Metric::Euclidean, Metric::Manhattan, Metric::Chebyshev] {
+                let mut expected: Vec<Neighbour> = points
+                    .iter()
+                    .enumerate()
+                    .map(|(index, point)| Neighbour {
+                        distance: metric.distance(point, &query),
+                        index,
+                    })
+                    .collect();
+                expected.sort_unstable();
+
+                for k in [1, 7, 500, 600] {
+                    let expected = &expected[..k.min(points.len())];
+                    assert_eq!(tree.nearest_by(&query, k, metric), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_metrics() {
+        let (a, b) = ([1, -2, 3], [4, 2, 3]);
+        assert_eq!(Metric::Euclidean.distance(&a, &b), 25);
+        assert_eq!(Metric::Euclidean.length(25), 5.0);
+        assert_eq!(Metric::Manhattan.distance(&a, &b), 7);
+        assert_eq!(Metric::Chebyshev.distance(&a, &b), 4);
+        assert_eq!("Manhattan".parse(), Ok(Metric::Manhattan));
+        assert!("taxicab".parse::<Metric>().is_err());
+    }
+
+    #[test]
+    fn test_within_radius_and_box() {
+        let points = generate_points(400, 100);
+        let tree = KdTree::new(points.clone());
+        let query = [50, 50, 50];
+
+        let expected: Vec<usize> = (0..points.len())
+            .filter(|&index| squared_distance(&points[index], &query) <= 400)
+            .collect();
+        assert_eq!(tree.within_radius(&query, 400), expected);
+
+        let (min, max) = ([10, 20, 30], [60, 70, 80]);
+        let expected: Vec<usize> = (0..points.len())
+            .filter(|&index| {
+                (0..3).all(|axis| (min[axis]..=max[axis]).contains(&points[index][axis]))
+            })
+            .collect();
+        assert_eq!(tree.within_box(&min, &max), expected);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one dimension")]
+    fn test_zero_dimensions() {
+        KdTree::new(vec![Vec::<i64>::new()]);
+    }
+
+    #[test]
+    fn test_distance_limits() {
+        let (min, max) = ([-(1 << 24); 4096], [1 << 24; 4096]);
+        assert_eq!(Metric::Euclidean.distance(&min, &max), 1 << 62);
+        assert_eq!(Metric::Chebyshev.distance(&min, &max), 1 << 25);
+    }
+
+    #[test]
+    #[should_panic(expected = "overflows")]
+    fn test_overflowing_distance() {
+        Metric::Manhattan.distance(&[i64::MIN], &[1]);
+    }
+
+    #[test]
+    fn test_two_dimensions() {
+        let tree = KdTree::new(vec![vec![0, 0], vec![5, 5], vec![1, 1], vec![-3, 2]]);
+        let nearest: Vec<usize> = tree
+            .nearest(&vec![0, 1], 2)
+            .iter()
+            .map(|neighbour| neighbour.index)
+            .collect();
+        assert_eq!(nearest, vec![0, 2]);
+        assert_eq!(tree.within_box(&[-5, 0], &[1, 2]), vec![0, 2, 3]);
+        assert!(
+            KdTree::<[i64; 2]>::new(Vec::new())
+                .nearest(&[0, 0], 3)
+                .is_empty()
+        );
+    }
+}
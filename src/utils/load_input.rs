@@ -1,6 +1,52 @@
 use std::fs::read_to_string;
 use std::io::Result;
+use std::path::PathBuf;
+
+/// Returns the path of a file in the input directory of a day, like `input/day05/example.txt`.
+pub fn input_path(day: u32, file: &str) -> PathBuf {
+    PathBuf::from(format!("input/day{:0>2}/{}", day, file))
+}
 
 pub fn load_input(day: u32) -> Result<String> {
-    read_to_string(format!("input/day{:0>2}/input.txt", day))
+    read_to_string(input_path(day, "input.txt"))
+}
+
+/// Reads a file from the input directory of a day for a test.
+///
+/// Puzzle inputs and examples can't be shared, so a fresh checkout doesn't have them.
+/// A missing file is reported on stderr, bypassing the test output capture, instead of failing the test.
+#[cfg(test)]
+pub(crate) fn read_fixture(day: u32, file: &str) -> Option<String> {
+    use std::io::Write;
+
+    let path = input_path(day, file);
+    match read_to_string(&path) {
+        Ok(input) => Some(input),
+        Err(_) => {
+            let _ = writeln!(
+                std::io::stderr(),
+                "Skipping test: {} is missing, add it to run the test",
+                path.display()
+            );
+            None
+        }
+    }
+}
+
+/// Reads a file from the input directory of a day, `example.txt` by default,
+/// or returns from the test if the file is missing.
+#[cfg(test)]
+macro_rules! fixture_or_skip {
+    ($day:expr) => {
+        fixture_or_skip!($day, "example.txt")
+    };
+    ($day:expr, $file:expr) => {
+        match crate::read_fixture($day, $file) {
+            Some(input) => input,
+            None => return,
+        }
+    };
 }
+
+#[cfg(test)]
+pub(crate) use fixture_or_skip;
@@ -0,0 +1,46 @@
+use std::{error::Error, fmt::Display};
+
+/// An input that doesn't match the format of a day, or that its solution can't handle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The line the error was found on, starting at 1, if it belongs to a single line.
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            line: None,
+            message: message.into(),
+        }
+    }
+
+    pub fn at_line(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line: Some(line),
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "Line {}: {}", line, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// Unboxes a `ParseError` with its line, and keeps the message of the string errors the older parsers return.
+impl From<Box<dyn Error>> for ParseError {
+    fn from(error: Box<dyn Error>) -> Self {
+        match error.downcast::<ParseError>() {
+            Ok(error) => *error,
+            Err(error) => Self::new(error.to_string()),
+        }
+    }
+}
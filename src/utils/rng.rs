@@ -0,0 +1,80 @@
+use std::ops::RangeInclusive;
+
+/// A small deterministic xorshift generator, good enough for generating puzzle inputs.
+///
+/// The same seed always produces the same sequence on every platform,
+/// which keeps generated inputs reproducible.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // Scramble the seed with splitmix64, so that small or similar seeds give unrelated sequences
+        // and a seed of zero doesn't leave xorshift stuck at zero
+        let mut state = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        state = (state ^ (state >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        state = (state ^ (state >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        state ^= state >> 31;
+        Self {
+            state: state.max(1),
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// Returns a value within the inclusive range.
+    ///
+    /// # Panics
+    /// Panics if the range is empty.
+    pub fn range(&mut self, range: RangeInclusive<u64>) -> u64 {
+        let (start, end) = range.into_inner();
+        assert!(start <= end, "Cannot pick a value from an empty range");
+        match (end - start).checked_add(1) {
+            Some(span) => start + self.next_u64() % span,
+            None => self.next_u64(),
+        }
+    }
+
+    /// Returns an index below `len`.
+    ///
+    /// # Panics
+    /// Panics if `len` is zero.
+    pub fn index(&mut self, len: usize) -> usize {
+        assert!(len > 0, "Cannot pick an index of an empty collection");
+        self.range(0..=len as u64 - 1) as usize
+    }
+
+    /// Returns `true` with a probability of `numerator / denominator`.
+    pub fn chance(&mut self, numerator: u64, denominator: u64) -> bool {
+        self.next_u64() % denominator < numerator
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.index(items.len())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deterministic_and_in_range() {
+        let mut a = Rng::new(7);
+        let mut b = Rng::new(7);
+        let values: Vec<u64> = (0..100).map(|_| a.range(3..=9)).collect();
+        assert!(values.iter().all(|value| (3..=9).contains(value)));
+        assert_eq!(values, (0..100).map(|_| b.range(3..=9)).collect::<Vec<_>>());
+
+        assert_ne!(Rng::new(0).next_u64(), Rng::new(1).next_u64());
+        assert_eq!(Rng::new(5).range(4..=4), 4);
+        Rng::new(5).range(0..=u64::MAX);
+    }
+}
@@ -0,0 +1,132 @@
+/// A disjoint-set forest over the elements `0..len`.
+///
+/// Uses path compression and union by size, so every operation runs in amortized
+/// `O(α(n))`. The number of components and the size of each one are kept up to date
+/// on every union, which makes both queries constant time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    components: usize,
+}
+
+impl UnionFind {
+    /// Creates a forest where every element is a component of its own.
+    pub fn new(len: usize) -> Self {
+        Self {
+            parent: (0..len).collect(),
+            size: vec![1; len],
+            components: len,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// Returns the representative of the component containing `element`.
+    ///
+    /// # Panics
+    /// Panics if `element` is out of bounds.
+    pub fn find(&mut self, element: usize) -> usize {
+        let mut root = element;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+
+        // Point every element on the path directly to the root
+        let mut current = element;
+        while self.parent[current] != root {
+            current = std::mem::replace(&mut self.parent[current], root);
+        }
+        root
+    }
+
+    /// Merges the components containing `a` and `b`.
+    ///
+    /// # Returns
+    /// `true` if they were in different components before.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (root_a, root_b) = (self.find(a), self.find(b));
+        if root_a == root_b {
+            return false;
+        }
+
+        let (smaller, larger) = if self.size[root_a] < self.size[root_b] {
+            (root_a, root_b)
+        } else {
+            (root_b, root_a)
+        };
+        self.parent[smaller] = larger;
+        self.size[larger] += self.size[smaller];
+        self.components -= 1;
+        true
+    }
+
+    pub fn connected(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// Returns the number of elements in the component containing `element`.
+    pub fn component_size(&mut self, element: usize) -> usize {
+        let root = self.find(element);
+        self.size[root]
+    }
+
+    pub fn component_count(&self) -> usize {
+        self.components
+    }
+
+    /// Returns the size of every component, in the order of their representatives.
+    pub fn component_sizes(&self) -> Vec<usize> {
+        self.parent
+            .iter()
+            .enumerate()
+            .filter(|&(element, &parent)| element == parent)
+            .map(|(root, _)| self.size[root])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_union_and_find() {
+        let mut forest = UnionFind::new(6);
+        assert_eq!(forest.component_count(), 6);
+
+        assert!(forest.union(0, 1));
+        assert!(forest.union(2, 3));
+        assert!(forest.union(1, 3));
+        assert!(!forest.union(0, 2));
+
+        assert!(forest.connected(0, 3));
+        assert!(!forest.connected(0, 4));
+        assert_eq!(forest.component_size(2), 4);
+        assert_eq!(forest.component_size(5), 1);
+        assert_eq!(forest.component_count(), 3);
+
+        let mut sizes = forest.component_sizes();
+        sizes.sort_unstable();
+        assert_eq!(sizes, vec![1, 1, 4]);
+    }
+
+    #[test]
+    fn test_long_chain_is_compressed() {
+        let mut forest = UnionFind::new(1000);
+        for element in 1..1000 {
+            forest.union(element - 1, element);
+        }
+        assert_eq!(forest.component_count(), 1);
+
+        let root = forest.find(0);
+        assert!((0..1000).all(|element| forest.parent[element] == root));
+        assert_eq!(forest.component_sizes(), vec![1000]);
+    }
+}